@@ -4,6 +4,15 @@ import timeGridPlugin from "@fullcalendar/timegrid";
 
 console.log(window.calendar);
 
+function eventTitle(event) {
+  const { category, module, rooms } = event.details;
+  if (!module) {
+    return event.description;
+  }
+  const title = category ? `${category} ${module}` : module;
+  return rooms.length > 0 ? `${title} - ${rooms.join(", ")}` : title;
+}
+
 let calendarEl = document.getElementById("calendar");
 let calendar = new Calendar(calendarEl, {
   plugins: [dayGridPlugin, timeGridPlugin],
@@ -20,7 +29,7 @@ let calendar = new Calendar(calendarEl, {
   weekends: false,
   height: "auto",
   events: window.calendar.map((event) => ({
-    title: event.description,
+    title: eventTitle(event),
     start: event.start,
     end: event.end,
     allDay: event.allDay,
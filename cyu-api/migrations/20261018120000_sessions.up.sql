@@ -0,0 +1,7 @@
+-- Sessions of the web interface, the browser only keeps their id
+CREATE TABLE `sessions` (
+  `id` TEXT NOT NULL PRIMARY KEY,
+  `userid` TEXT NOT NULL,
+  `token` TEXT NOT NULL,
+  `credentials` TEXT
+);
@@ -2,14 +2,17 @@ use crate::utils::Env;
 use anyhow::{Context as _, Result};
 use axum::extract::FromRef;
 use base64::Engine;
-use cyu_fetcher::Fetcher;
+use cyu_fetcher::sites::SiteRegistry;
+use cyu_fetcher::{CacheConfig, Fetcher, InstanceProfile};
 use handlebars::Handlebars;
 use rust_embed::Embed;
 use std::sync::Arc;
+use std::time::Duration;
 
 pub type TemplateEngine = Arc<Handlebars<'static>>;
 pub type Encrypter = Arc<auth_token::Encrypter>;
 pub type Database = Arc<sqlx::SqlitePool>;
+pub type Profile = Arc<InstanceProfile>;
 
 #[derive(Embed)]
 #[folder = "assets/views"]
@@ -37,6 +40,7 @@ pub struct App {
     pub env: Env,
     pub encrypter: Encrypter,
     pub database: Database,
+    pub profile: Profile,
 }
 
 impl App {
@@ -62,12 +66,36 @@ impl App {
             .unwrap();
         let encrypter = auth_token::Encrypter::new(&key).unwrap();
         let database = sqlx::SqlitePool::connect(&env.database_url).await.unwrap();
+        let mut profile = match &env.celcat_profile {
+            Some(path) => InstanceProfile::from_file(path).context("Failed to load profile")?,
+            None => InstanceProfile::cyu().clone(),
+        };
+        if let Some(base_url) = &env.cyu_base_url {
+            profile.base_url = base_url.parse().context("Invalid CYU_BASE_URL")?;
+        }
+        if let Some(path) = &env.sites_file {
+            let sites = SiteRegistry::from_file(path).context("Failed to load sites file")?;
+            profile.sites = profile.sites.merge(sites);
+        }
+        let mut fetcher = Fetcher::builder().profile(&profile);
+        match env.cache_ttl {
+            Some(0) => {}
+            Some(ttl) => {
+                fetcher = fetcher.cache(CacheConfig {
+                    ttl: Duration::from_secs(ttl),
+                    ..Default::default()
+                })
+            }
+            None => fetcher = fetcher.cache(CacheConfig::default()),
+        }
+        let fetcher = fetcher.build().context("Failed to build fetcher")?;
         Ok(Self {
-            requester: Fetcher::new(),
+            requester: fetcher,
             template_engine: handlebars.into(),
             env,
             encrypter: encrypter.into(),
             database: database.into(),
+            profile: profile.into(),
         })
     }
 }
@@ -101,3 +129,9 @@ impl FromRef<App> for Database {
         app.database.clone()
     }
 }
+
+impl FromRef<App> for Profile {
+    fn from_ref(app: &App) -> Self {
+        app.profile.clone()
+    }
+}
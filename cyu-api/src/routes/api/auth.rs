@@ -1,14 +1,17 @@
-use crate::app::App;
-use crate::utils::response::api_error;
+use crate::app::{App, Database, Encrypter};
+use crate::utils::auth::{encrypt_credentials, save_renewed_session, start_session};
+use crate::utils::response::{api_error, api_fetcher_error};
 use crate::utils::Auth;
 use axum::extract::State;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::{Json, Router};
-use cyu_fetcher::Fetcher;
+use cyu_fetcher::auth::StudentProfile;
+use cyu_fetcher::utils::CyuDate;
+use cyu_fetcher::{Credentials, Fetcher, Session};
 use serde::{Deserialize, Serialize};
-use tower_cookies::{Cookie, Cookies};
+use tower_cookies::Cookies;
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub(super) struct LoginPayload {
@@ -24,52 +27,85 @@ struct LoginResponse {
 async fn login(
     cookies: Cookies,
     State(fetcher): State<Fetcher>,
+    State(encrypter): State<Encrypter>,
+    State(database): State<Database>,
     Json(payload): Json<LoginPayload>,
 ) -> Response {
-    let token = match fetcher.login(payload.username, payload.password).await {
-        Ok(token) => token,
-        Err(_) => {
+    let session = Session::new(
+        fetcher,
+        Credentials {
+            username: payload.username.clone(),
+            password: payload.password.clone(),
+        },
+    );
+    let identity = match session.login().await {
+        Ok(identity) => identity,
+        Err(cyu_fetcher::Error::Unauthorized) => {
             return api_error(StatusCode::UNAUTHORIZED, "Invalid credentials").into_response()
         }
+        Err(err) => return api_fetcher_error("Failed to login to cyu", &err).into_response(),
     };
-    let infos = match fetcher.get_infos(token.clone()).await {
-        Ok(infos) => infos,
-        Err(_) => {
-            return api_error(
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Failed to retrieve informations",
-            )
-            .into_response()
-        }
-    };
-    cookies.add(Cookie::build(("token", token)).path("/").build());
-    cookies.add(Cookie::build(("id", infos.federation_id)).path("/").build());
+    let credentials = encrypt_credentials(&encrypter, &payload);
+    if let Err(err) = start_session(&cookies, &database, &encrypter, identity, credentials).await {
+        eprintln!("{err:#}");
+        return api_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to save session")
+            .into_response();
+    }
     Json(LoginResponse { success: true }).into_response()
 }
 
+#[derive(Debug, Serialize)]
+struct DateExtents {
+    start: CyuDate,
+    end: CyuDate,
+}
+
 #[derive(Debug, Serialize)]
 struct GetInfosResponse {
     id: String,
     name: String,
+    groups: Vec<String>,
+    programme: Option<String>,
+    department: Option<String>,
+    /// First and last days the calendar can be browsed.
+    date_extents: DateExtents,
 }
 
-async fn get_infos(auth: Auth, State(fetcher): State<Fetcher>) -> Response {
-    let infos = match fetcher.get_infos(auth.token).await {
-        Ok(infos) => infos,
-        Err(_) => {
-            return api_error(
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Failed to retrieve informations",
-            )
-            .into_response()
+impl From<StudentProfile> for GetInfosResponse {
+    fn from(profile: StudentProfile) -> Self {
+        Self {
+            id: profile.federation_id,
+            name: profile.display_name,
+            groups: profile.groups,
+            programme: profile.programme,
+            department: profile.department,
+            date_extents: DateExtents {
+                start: profile.date_extents.0,
+                end: profile.date_extents.1,
+            },
+        }
+    }
+}
+
+/// Infos of the student, with what their calendar tells about them.
+async fn get_infos(
+    auth: Auth,
+    State(fetcher): State<Fetcher>,
+    State(encrypter): State<Encrypter>,
+) -> Response {
+    let session = auth.session(fetcher, encrypter.clone());
+    let infos = match session.get_profile().await {
+        Ok(profile) => GetInfosResponse::from(profile),
+        Err(cyu_fetcher::Error::Unauthorized) => {
+            return api_error(StatusCode::UNAUTHORIZED, "Session expired").into_response()
+        }
+        Err(err) => {
+            return api_fetcher_error("Failed to retrieve informations", &err).into_response()
         }
     };
+    save_renewed_session(&session, &auth, &encrypter).await;
 
-    Json(GetInfosResponse {
-        id: infos.federation_id,
-        name: infos.display_name,
-    })
-    .into_response()
+    Json(infos).into_response()
 }
 
 pub fn routes() -> Router<App> {
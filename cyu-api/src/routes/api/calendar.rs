@@ -1,13 +1,15 @@
-use crate::app::{App, Database, Encrypter};
+use crate::app::{App, Database, Encrypter, Profile};
+use crate::utils::auth::save_renewed_session;
 use crate::utils::body::Body;
-use crate::utils::response::api_error;
+use crate::utils::response::{api_error, api_fetcher_error};
 use crate::utils::{ics, Auth};
 use axum::extract::{Query, State};
 use axum::http::{header, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::{Json, Router};
-use cyu_fetcher::{calendar::ColorBy, utils::CyuDate, Fetcher};
+use cyu_fetcher::calendar::{ColorBy, ResourceType};
+use cyu_fetcher::{utils::CyuDate, Credentials, Fetcher, Session};
 use serde::Deserialize;
 use std::ops::Deref;
 
@@ -18,31 +20,77 @@ struct GetCalendarQuery {
     start: CyuDate,
     end: CyuDate,
     view: cyu_fetcher::calendar::CalendarView,
+    /// Timetable of a room, a teacher, ... rather than the user's.
+    resource_id: Option<String>,
+    #[serde(default)]
+    resource_type: ResourceType,
 }
 
 async fn get_calendar(
     Query(query): Query<GetCalendarQuery>,
     auth: Auth,
     State(fetcher): State<Fetcher>,
+    State(encrypter): State<Encrypter>,
 ) -> Response {
-    let calendar = fetcher
-        .get_calendar(cyu_fetcher::calendar::GetCalendarQuery {
-            id: auth.id,
-            token: auth.token,
-            start: query.start,
-            end: query.end,
-            view: query.view,
-            color_by: ColorBy::EventCategory,
-        })
-        .await;
+    let session = auth.session(fetcher, encrypter.clone());
+    let calendar = match &query.resource_id {
+        Some(id) => {
+            session
+                .get_resource_calendar(
+                    query.resource_type,
+                    id,
+                    query.start,
+                    query.end,
+                    query.view,
+                    ColorBy::EventCategory,
+                )
+                .await
+        }
+        None => {
+            session
+                .get_calendar(query.start, query.end, query.view, ColorBy::EventCategory)
+                .await
+        }
+    };
 
     match calendar {
-        Ok(calendar) => Json(calendar).into_response(),
-        Err(_) => api_error(
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Failed to retrieve calendar from cyu",
-        )
-        .into_response(),
+        Ok(calendar) => {
+            save_renewed_session(&session, &auth, &encrypter).await;
+            Json(calendar).into_response()
+        }
+        Err(cyu_fetcher::Error::Unauthorized) => {
+            api_error(StatusCode::UNAUTHORIZED, "Session expired").into_response()
+        }
+        Err(err) => api_fetcher_error("Failed to retrieve calendar from cyu", &err).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchResourcesQuery {
+    resource_type: ResourceType,
+    #[serde(default)]
+    q: String,
+}
+
+async fn search_resources(
+    Query(query): Query<SearchResourcesQuery>,
+    auth: Auth,
+    State(fetcher): State<Fetcher>,
+    State(encrypter): State<Encrypter>,
+) -> Response {
+    let session = auth.session(fetcher, encrypter.clone());
+    match session
+        .search_resources(query.resource_type, &query.q)
+        .await
+    {
+        Ok(resources) => {
+            save_renewed_session(&session, &auth, &encrypter).await;
+            Json(resources).into_response()
+        }
+        Err(cyu_fetcher::Error::Unauthorized) => {
+            api_error(StatusCode::UNAUTHORIZED, "Session expired").into_response()
+        }
+        Err(err) => api_fetcher_error("Failed to search resources on cyu", &err).into_response(),
     }
 }
 
@@ -95,36 +143,35 @@ async fn delete_ics_token(
 #[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
 struct GetIcsQuery {
     token: String,
+    /// Export weekly sessions as recurring events.
+    #[serde(default)]
+    collapse: bool,
 }
 
 async fn get_ics(
     State(encrypter): State<Encrypter>,
     State(fetcher): State<Fetcher>,
+    State(profile): State<Profile>,
     // auth: Auth,
     Query(query): Query<GetIcsQuery>,
 ) -> Response {
     let Ok((username, password)) = encrypter.decrypt(&query.token) else {
         return (StatusCode::UNAUTHORIZED, "").into_response();
     };
-    let Ok(token) = fetcher.login(username, password).await else {
-        return (StatusCode::UNAUTHORIZED, "").into_response();
-    };
-    let Ok(infos) = fetcher.get_infos(token.clone()).await else {
-        return (StatusCode::UNAUTHORIZED, "").into_response();
-    };
-    let auth = Auth {
-        id: infos.federation_id,
-        token,
-    };
-    let Ok(calendar) = ics::generate(&fetcher, auth).await else {
-        return (StatusCode::INTERNAL_SERVER_ERROR, "").into_response();
-    };
-    ([(header::CONTENT_TYPE, "text/calendar")], calendar).into_response()
+    let session = Session::new(fetcher, Credentials { username, password });
+    match ics::generate(&session, &profile, query.collapse).await {
+        Ok(calendar) => ([(header::CONTENT_TYPE, "text/calendar")], calendar).into_response(),
+        Err(err) if matches!(err.downcast_ref(), Some(cyu_fetcher::Error::Unauthorized)) => {
+            (StatusCode::UNAUTHORIZED, "").into_response()
+        }
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "").into_response(),
+    }
 }
 
 pub fn routes() -> Router<App> {
     Router::new()
         .route("/", get(get_calendar))
+        .route("/resources", get(search_resources))
         .route("/ics", get(get_ics))
         .route("/ics-token", post(post_ics_token).delete(delete_ics_token))
 }
@@ -1,10 +1,10 @@
 use super::{check_auth, default_date_for_view, render_template_or_fail};
-use crate::app::{App, TemplateEngine};
+use crate::app::{App, Database, Encrypter, TemplateEngine};
 use crate::routes::ui::set_uri;
-use crate::utils::response::{redirect_to_login, ui_error};
+use crate::utils::auth::{end_session, save_renewed_session};
+use crate::utils::response::{redirect_to_login, ui_fetcher_error};
 use crate::utils::Auth;
 use axum::extract::{OriginalUri, Query, State};
-use axum::http::StatusCode;
 use axum::middleware;
 use axum::response::{IntoResponse as _, Redirect, Response};
 use axum::routing::get;
@@ -13,11 +13,17 @@ use cyu_fetcher::utils::CyuDate;
 use cyu_fetcher::Fetcher;
 use derive_more::Display;
 use serde::{Deserialize, Serialize};
-use tower_cookies::{Cookie, Cookies};
+use tower_cookies::Cookies;
 
+#[derive(Serialize)]
+struct HomeEvent {
+    #[serde(flatten)]
+    event: cyu_fetcher::calendar::GetCalendarResponseElement,
+    details: cyu_fetcher::calendar::EventDetails,
+}
 #[derive(Serialize)]
 struct HomeData {
-    calendar: cyu_fetcher::calendar::GetCalendarResponse,
+    calendar: Vec<HomeEvent>,
     previous_page: String,
     next_page: String,
 }
@@ -51,6 +57,8 @@ async fn home(
     Query(query): Query<HomeQuery>,
     State(te): State<TemplateEngine>,
     State(fetcher): State<Fetcher>,
+    State(encrypter): State<Encrypter>,
+    State(database): State<Database>,
 ) -> Response {
     let uri_string = uri.to_string();
     let (start, view) = match (query.date, query.view) {
@@ -62,14 +70,14 @@ async fn home(
             return Redirect::to(&set_uri(&uri_string, &date, &view)).into_response();
         }
         (None, Some(view)) => {
-            let date = default_date_for_view(&view);
+            let date = default_date_for_view(&view, CyuDate::today_in(fetcher.timezone()));
             // return Ok(Redirect::to(&format!("{uri}&date={date}")).into_response())
             return Redirect::to(&set_uri(&uri_string, &date, &view)).into_response();
         }
         (None, None) => {
             // let sep = if uri.query().is_some() {'&'} else {'?'};
             let view = HomeQueryView::default();
-            let date = default_date_for_view(&view);
+            let date = default_date_for_view(&view, CyuDate::today_in(fetcher.timezone()));
             // return Ok(Redirect::to(&format!("{uri}{sep}date={date}&view={view}")).into_response())
             return Redirect::to(&set_uri(&uri_string, &date, &view)).into_response();
         }
@@ -103,29 +111,29 @@ async fn home(
     let previous_page = set_uri(&uri_string, &previous, &view);
     let next_page = set_uri(&uri_string, &next, &view);
 
-    let calendar = fetcher
-        .get_all_calendar(cyu_fetcher::calendar::GetAllQuery {
-            id: auth.id,
-            token: auth.token,
-            color_by: cyu_fetcher::calendar::ColorBy::EventCategory,
-        })
+    let session = auth.session(fetcher, encrypter.clone());
+    let calendar = session
+        .get_all_calendar(cyu_fetcher::calendar::ColorBy::EventCategory)
         .await;
 
     let calendar = match calendar {
-        Ok(calendar) => calendar,
+        Ok(calendar) => calendar
+            .events
+            .into_iter()
+            .map(|event| HomeEvent {
+                details: event.details(),
+                event,
+            })
+            .collect(),
         Err(cyu_fetcher::Error::Unauthorized) => {
-            cookies.remove(Cookie::from("token"));
-            cookies.remove(Cookie::from("id"));
+            end_session(&cookies, &database).await;
             return redirect_to_login(&uri).into_response();
         }
-        Err(_) => {
-            return ui_error(
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Failed to retrieve calendar from cyu".to_owned(),
-            )
-            .into_response()
+        Err(err) => {
+            return ui_fetcher_error("Failed to retrieve calendar from cyu", &err).into_response()
         }
     };
+    save_renewed_session(&session, &auth, &encrypter).await;
 
     render_template_or_fail(
         te,
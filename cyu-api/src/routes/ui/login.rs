@@ -1,22 +1,23 @@
 use super::{check_unauth, render_template_or_fail};
-use crate::app::{App, TemplateEngine};
-use crate::utils::response::ui_error;
+use crate::app::{App, Database, Encrypter, TemplateEngine};
+use crate::utils::auth::{encrypt_credentials, start_session};
+use crate::utils::response::{ui_error, ui_fetcher_error};
 use axum::extract::{Query, State};
 use axum::http::StatusCode;
 use axum::response::{IntoResponse as _, Redirect, Response};
 use axum::routing::get;
 use axum::{middleware, Form};
-use cyu_fetcher::Fetcher;
-use serde::Deserialize;
+use cyu_fetcher::{Credentials, Fetcher, Session};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tower_cookies::{Cookie, Cookies};
+use tower_cookies::Cookies;
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
 pub(super) struct LoginQuery {
     pub(super) redirect: Option<String>,
 }
 
-#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 struct LoginHandlePayload {
     username: String,
     password: String,
@@ -30,34 +31,34 @@ async fn login_handle(
     cookies: Cookies,
     Query(query): Query<LoginQuery>,
     State(fetcher): State<Fetcher>,
+    State(encrypter): State<Encrypter>,
+    State(database): State<Database>,
     Form(payload): Form<LoginHandlePayload>,
 ) -> Response {
-    let token = match fetcher.login(payload.username, payload.password).await {
-        Ok(token) => token,
+    let session = Session::new(
+        fetcher,
+        Credentials {
+            username: payload.username.clone(),
+            password: payload.password.clone(),
+        },
+    );
+    let identity = match session.login().await {
+        Ok(identity) => identity,
         Err(cyu_fetcher::errors::Error::Unauthorized) => {
             return ui_error(StatusCode::UNAUTHORIZED, "Invalid credentials".to_owned())
                 .into_response()
         }
-        Err(_) => {
-            return ui_error(
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Failed to login to cyu".to_owned(),
-            )
-            .into_response()
-        }
-    };
-    let infos = match fetcher.get_infos(token.clone()).await {
-        Ok(infos) => infos,
-        Err(_) => {
-            return ui_error(
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Failed to retrieve infos from cyu".to_owned(),
-            )
-            .into_response()
-        }
+        Err(err) => return ui_fetcher_error("Failed to login to cyu", &err).into_response(),
     };
-    cookies.add(Cookie::new("token", token));
-    cookies.add(Cookie::new("id", infos.federation_id));
+    let credentials = encrypt_credentials(&encrypter, &payload);
+    if let Err(err) = start_session(&cookies, &database, &encrypter, identity, credentials).await {
+        eprintln!("{err:#}");
+        return ui_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to save session".to_owned(),
+        )
+        .into_response();
+    }
     Redirect::to(&query.redirect.unwrap_or("/".into())).into_response()
 }
 
@@ -3,7 +3,7 @@ mod links;
 mod login;
 
 use crate::app::{App, TemplateEngine};
-use crate::utils::auth::get_auth_from_cookies;
+use crate::utils::auth::get_session_id;
 use crate::utils::response::{redirect_to_login, AnyhowExt as _};
 use anyhow::Context;
 use axum::extract::{OriginalUri, Query, Request};
@@ -55,7 +55,7 @@ async fn check_auth(
     request: Request,
     next: Next,
 ) -> Response {
-    if get_auth_from_cookies(&cookies).is_none() {
+    if get_session_id(&cookies).is_none() {
         redirect_to_login(&uri).into_response()
     } else {
         next.run(request).await
@@ -68,14 +68,13 @@ async fn check_unauth(
     request: Request,
     next: Next,
 ) -> Response {
-    if get_auth_from_cookies(&cookies).is_some() {
+    if get_session_id(&cookies).is_some() {
         return Redirect::to(&query.redirect.unwrap_or("/".into())).into_response();
     }
     next.run(request).await
 }
 
-fn default_date_for_view(view: &HomeQueryView) -> CyuDate {
-    let today = CyuDate::today();
+fn default_date_for_view(view: &HomeQueryView, today: CyuDate) -> CyuDate {
     match view {
         HomeQueryView::Month => today.with_day(1).unwrap().into(),
         HomeQueryView::Week => match today.weekday() {
@@ -1,24 +1,207 @@
-use anyhow::Result;
+use crate::app::{Database, Encrypter};
+use anyhow::{anyhow, Result};
+use axum::extract::FromRef;
+use axum::http::StatusCode;
 use axum::{extract::FromRequestParts, http::request::Parts, RequestPartsExt};
-use tower_cookies::Cookies;
+use base64::Engine as _;
+use cyu_fetcher::{CredentialProvider, Credentials, Fetcher, Identity, Session, SessionCookies};
+use futures::future::BoxFuture;
+use serde::Serialize;
+use tower_cookies::cookie::SameSite;
+use tower_cookies::{Cookie, Cookies};
+
+/// Cookie holding the id of the server-side session.
+const SESSION_COOKIE: &str = "session";
+/// Cookies of older versions, which held the session and the credentials
+/// themselves.
+const LEGACY_COOKIES: [&str; 3] = ["token", "id", "credentials"];
 
 pub struct Auth {
-    pub token: String,
+    /// Row of the `sessions` table the session cookie points to.
+    session_id: String,
+    /// Encrypted cookies of the CYU session.
+    pub cyu_session: String,
     pub id: String,
+    /// Encrypted credentials, used to log in again when the session expires.
+    pub credentials: Option<String>,
+    database: Database,
+}
+
+/// Id of the session the cookies point to, which may no longer exist.
+pub fn get_session_id(cookies: &Cookies) -> Option<String> {
+    Some(cookies.get(SESSION_COOKIE)?.value().to_owned())
+}
+
+/// A random id that cannot be guessed.
+fn new_session_id() -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>())
+}
+
+fn remove_legacy_cookies(cookies: &Cookies) {
+    for name in LEGACY_COOKIES {
+        if cookies.get(name).is_some() {
+            cookies.remove(Cookie::build(name).path("/").build());
+        }
+    }
+}
+
+/// Stores what the other requests need to act on behalf of the user in the
+/// database, the browser only getting the id of the row.
+pub async fn start_session(
+    cookies: &Cookies,
+    database: &Database,
+    encrypter: &Encrypter,
+    identity: Identity,
+    credentials: Option<String>,
+) -> Result<()> {
+    let token = encrypter
+        .encrypt(&identity.token)
+        .map_err(|_| anyhow!("Failed to encrypt session"))?;
+    let id = new_session_id();
+    sqlx::query!(
+        "INSERT INTO sessions (id, userid, token, credentials) VALUES (?, ?, ?, ?)",
+        id,
+        identity.federation_id,
+        token,
+        credentials
+    )
+    .execute(&**database)
+    .await?;
+
+    remove_legacy_cookies(cookies);
+    cookies.add(
+        Cookie::build((SESSION_COOKIE, id))
+            .path("/")
+            .http_only(true)
+            .secure(true)
+            .same_site(SameSite::Strict)
+            .build(),
+    );
+    Ok(())
+}
+
+/// Forgets the session of the cookies, on both sides.
+pub async fn end_session(cookies: &Cookies, database: &Database) {
+    if let Some(id) = get_session_id(cookies) {
+        let result = sqlx::query!("DELETE FROM sessions WHERE id = ?", id)
+            .execute(&**database)
+            .await;
+        if let Err(err) = result {
+            eprintln!("Failed to delete session: {err}");
+        }
+    }
+    cookies.remove(Cookie::build(SESSION_COOKIE).path("/").build());
+    remove_legacy_cookies(cookies);
 }
 
-pub fn get_auth_from_cookies(cookies: &Cookies) -> Option<Auth> {
-    let token = cookies.get("token")?.value().to_owned();
-    let id = cookies.get("id")?.value().to_owned();
-    Some(Auth { token, id })
+pub fn encrypt_credentials(encrypter: &Encrypter, credentials: &impl Serialize) -> Option<String> {
+    match encrypter.encrypt(credentials) {
+        Ok(credentials) => Some(credentials),
+        Err(_) => {
+            eprintln!("Failed to encrypt credentials");
+            None
+        }
+    }
+}
+
+struct StoredCredentials {
+    encrypter: Encrypter,
+    credentials: Option<String>,
+}
+
+impl CredentialProvider for StoredCredentials {
+    fn credentials(&self) -> BoxFuture<'_, Option<Credentials>> {
+        let credentials = self.credentials.as_deref().and_then(|credentials| {
+            let (username, password): (String, String) =
+                self.encrypter.decrypt(credentials).ok()?;
+            Some(Credentials { username, password })
+        });
+        Box::pin(std::future::ready(credentials))
+    }
+}
+
+impl Auth {
+    /// A session resuming the stored CYU session, that logs in again with
+    /// the stored credentials when it expired.
+    pub fn session(&self, fetcher: Fetcher, encrypter: Encrypter) -> Session {
+        let token = encrypter.decrypt::<SessionCookies>(&self.cyu_session).ok();
+        let provider = StoredCredentials {
+            encrypter,
+            credentials: self.credentials.clone(),
+        };
+        let session = Session::new(fetcher, provider);
+        match token {
+            Some(token) => session.resume(Identity {
+                token,
+                federation_id: self.id.clone(),
+            }),
+            None => session,
+        }
+    }
+}
+
+/// Saves the CYU session of `session` if its cookies changed, renewed by CYU
+/// or by logging in again.
+pub async fn save_renewed_session(session: &Session, auth: &Auth, encrypter: &Encrypter) {
+    let Ok(identity) = session.identity().await else {
+        return;
+    };
+    let saved = encrypter.decrypt::<SessionCookies>(&auth.cyu_session).ok();
+    if saved.as_ref() == Some(&identity.token) {
+        return;
+    }
+    let Ok(token) = encrypter.encrypt(&identity.token) else {
+        eprintln!("Failed to encrypt session");
+        return;
+    };
+    let result = sqlx::query!(
+        "UPDATE sessions SET token = ? WHERE id = ?",
+        token,
+        auth.session_id
+    )
+    .execute(&*auth.database)
+    .await;
+    if let Err(err) = result {
+        eprintln!("Failed to save session: {err}");
+    }
 }
 
-impl<S: Send + Sync> FromRequestParts<S> for Auth {
-    type Rejection = ();
+impl<S: Send + Sync> FromRequestParts<S> for Auth
+where
+    Database: FromRef<S>,
+{
+    type Rejection = StatusCode;
 
-    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, ()> {
-        let cookies = parts.extract::<Cookies>().await.map_err(|_| ())?;
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, StatusCode> {
+        let cookies = parts
+            .extract::<Cookies>()
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        let session_id = get_session_id(&cookies).ok_or(StatusCode::UNAUTHORIZED)?;
+        let database = Database::from_ref(state);
+        let row = sqlx::query!(
+            "SELECT userid, token, credentials FROM sessions WHERE id = ?",
+            session_id
+        )
+        .fetch_optional(&*database)
+        .await
+        .map_err(|err| {
+            eprintln!("Failed to fetch session: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        let Some(row) = row else {
+            // Deleted, or left by an older version
+            cookies.remove(Cookie::build(SESSION_COOKIE).path("/").build());
+            return Err(StatusCode::UNAUTHORIZED);
+        };
+        remove_legacy_cookies(&cookies);
 
-        get_auth_from_cookies(&cookies).ok_or(())
+        Ok(Auth {
+            session_id,
+            cyu_session: row.token,
+            id: row.userid,
+            credentials: row.credentials,
+            database,
+        })
     }
 }
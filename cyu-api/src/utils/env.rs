@@ -5,6 +5,14 @@ pub struct Env {
     pub port: u16,
     pub ics_auth_key: String,
     pub database_url: String,
+    /// TOML file describing the Celcat instance to use, CYU when unset.
+    pub celcat_profile: Option<String>,
+    /// Overrides the base URL of the profile.
+    pub cyu_base_url: Option<String>,
+    /// Seconds during which CYU answers are reused, 0 disables the cache.
+    pub cache_ttl: Option<u64>,
+    /// TOML or JSON file adding sites to the ones of the profile.
+    pub sites_file: Option<String>,
 }
 
 macro_rules! load_env {
@@ -22,6 +30,14 @@ impl Env {
             port: load_env!(PORT).parse()?,
             ics_auth_key: load_env!(ICS_AUTH_KEY),
             database_url: load_env!(DATABASE_URL),
+            celcat_profile: std::env::var("CELCAT_PROFILE").ok(),
+            cyu_base_url: std::env::var("CYU_BASE_URL").ok(),
+            cache_ttl: std::env::var("CACHE_TTL")
+                .ok()
+                .map(|ttl| ttl.parse())
+                .transpose()
+                .context("CACHE_TTL is not a number of seconds")?,
+            sites_file: std::env::var("SITES_FILE").ok(),
         })
     }
 }
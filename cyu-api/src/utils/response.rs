@@ -21,6 +21,33 @@ pub fn ui_error(status_code: StatusCode, error: String) -> UiResponse {
     (status_code, error)
 }
 
+fn log_fetcher_error(error: &cyu_fetcher::Error) {
+    if error.is_layout_change() {
+        eprintln!("CYU scraper is out of date: {error}");
+        if let Some(fragment) = error.fragment() {
+            eprintln!("Near:\n{fragment}");
+        }
+    } else {
+        eprintln!("CYU request failed: {error}");
+    }
+}
+
+pub fn api_fetcher_error(context: &str, error: &cyu_fetcher::Error) -> ApiResponse {
+    log_fetcher_error(error);
+    api_error(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        &format!("{context}: {error}"),
+    )
+}
+
+pub fn ui_fetcher_error(context: &str, error: &cyu_fetcher::Error) -> UiResponse {
+    log_fetcher_error(error);
+    ui_error(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        format!("{context}: {error}"),
+    )
+}
+
 pub fn redirect_to_login(current_uri: &Uri) -> impl IntoResponse {
     let redirect = current_uri
         .path_and_query()
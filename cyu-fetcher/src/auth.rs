@@ -1,37 +1,45 @@
+use crate::backend::{Backend, Direct};
+use crate::calendar::{
+    ColorBy, GetCalendarResponseElement, GetLimitsQuery, GetRangeQuery, RangeOptions, ResourceType,
+};
+use crate::cookies::{store_response_cookies, SessionCookies};
 use crate::errors::Error;
-use itertools::Itertools;
-use once_cell::sync::Lazy;
-use regex::Regex;
-use serde::Deserialize;
+use crate::scrape;
+use crate::utils::{AcademicYear, CyuDate};
+use chrono::Days;
+use futures::future::BoxFuture;
+use reqwest::cookie::{CookieStore, Jar};
+use reqwest::header::{COOKIE, LOCATION};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::collections::HashMap;
 
-pub async fn login(
+/// Submits the login form, keeping the cookies CYU sets along the way in
+/// `cookies`.
+async fn logon(
     requester: &reqwest::Client,
+    base_url: &str,
     username: String,
     password: String,
-) -> Result<String, Error> {
+    cookies: &dyn CookieStore,
+) -> Result<(), Error> {
     let page_response = requester
-        .get("https://services-web.cyu.fr/calendar/LdapLogin")
+        .get(format!("{base_url}/LdapLogin"))
         .send()
         .await
-        .map_err(|_| Error::Remote)?;
-    let page_cookie = page_response
-        .headers()
-        .get("set-cookie")
-        .map(|cookie| cookie.clone())
-        .ok_or(Error::Remote)?;
-    let plain_text = page_response.text().await.map_err(|_| Error::Remote)?;
-
-    static SECURITY_TOKEN_REGEX: Lazy<Regex> = Lazy::new(|| {
-        Regex::new(r#"<input name="__RequestVerificationToken" type="hidden" value="([^"]+)" />"#)
-            .unwrap()
-    });
-    let token = SECURITY_TOKEN_REGEX
-        .captures(&plain_text)
-        .and_then(|captures| captures.get(1))
-        .map(|token| token.as_str().to_string())
-        .ok_or(Error::Remote)?;
+        .map_err(Error::Network)?;
+    if !page_response.status().is_success() {
+        return Err(Error::status(&page_response));
+    }
+    store_response_cookies(cookies, &page_response);
+    let page_cookies = cookies.cookies(page_response.url()).ok_or(Error::Layout {
+        page: "LdapLogin",
+        marker: "set-cookie header",
+        fragment: None,
+    })?;
+    let plain_text = page_response.text().await.map_err(Error::Network)?;
+    let token = scrape::verification_token(&plain_text)?;
 
     let mut remote_payload = HashMap::new();
     remote_payload.insert("Name", username);
@@ -39,73 +47,429 @@ pub async fn login(
     remote_payload.insert("__RequestVerificationToken", token);
 
     let login_response = requester
-        .post("https://services-web.cyu.fr/calendar/LdapLogin/Logon")
+        .post(format!("{base_url}/LdapLogin/Logon"))
         .form(&remote_payload)
         .header("Content-Type", "application/x-www-form-urlencoded")
-        .header("Cookie", page_cookie)
+        .header(COOKIE, page_cookies)
         .send()
         .await
-        .map_err(|_| Error::Remote)?;
+        .map_err(Error::Network)?;
 
-    if !login_response.status().is_redirection() {
+    // A refused login renders the form again instead of redirecting
+    if login_response.status().is_success() {
         return Err(Error::Unauthorized);
     }
+    if !login_response.status().is_redirection() {
+        return Err(Error::status(&login_response));
+    }
 
-    let login_cookie = login_response
-        .headers()
-        .get_all("set-cookie")
-        .iter()
-        .map(|cookie| cookie.to_str().unwrap_or_default().to_string())
-        .join(";");
+    store_response_cookies(cookies, &login_response);
+    Ok(())
+}
+
+/// A way of opening a CYU session.
+///
+/// Strategies log in with a username and password and leave the session
+/// cookies in `cookies`, along with whatever the login pages set.
+pub trait AuthStrategy: Send + Sync {
+    fn login<'a>(
+        &'a self,
+        requester: &'a reqwest::Client,
+        base_url: &'a str,
+        username: String,
+        password: String,
+        cookies: &'a dyn CookieStore,
+    ) -> BoxFuture<'a, Result<(), Error>>;
+}
 
-    Ok(login_cookie)
+/// The LDAP form of Celcat itself, at `/LdapLogin`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LdapLogin;
+
+impl AuthStrategy for LdapLogin {
+    fn login<'a>(
+        &'a self,
+        requester: &'a reqwest::Client,
+        base_url: &'a str,
+        username: String,
+        password: String,
+        cookies: &'a dyn CookieStore,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(logon(requester, base_url, username, password, cookies))
+    }
+}
+
+/// Single sign-on through a CAS server, which hands Celcat a service
+/// ticket to open the session with.
+#[derive(Debug, Clone)]
+pub struct CasLogin {
+    server_url: String,
+    service_path: String,
+}
+
+impl CasLogin {
+    /// `server_url` is the root of the CAS server, without trailing slash,
+    /// e.g. `https://sso.example.fr/cas`.
+    pub fn new(server_url: impl Into<String>) -> Self {
+        Self {
+            server_url: server_url.into().trim_end_matches('/').to_owned(),
+            service_path: String::from("/CasLogin"),
+        }
+    }
+
+    /// Page of the calendar the CAS server sends the ticket to, relative to
+    /// the base URL. Defaults to `/CasLogin`.
+    pub fn service_path(mut self, service_path: impl Into<String>) -> Self {
+        self.service_path = service_path.into();
+        self
+    }
+
+    pub fn server_url(&self) -> &str {
+        &self.server_url
+    }
+
+    async fn logon(
+        &self,
+        requester: &reqwest::Client,
+        base_url: &str,
+        username: String,
+        password: String,
+        cookies: &dyn CookieStore,
+    ) -> Result<(), Error> {
+        let service = format!("{base_url}{}", self.service_path);
+        let page_response = requester
+            .get(format!("{}/login", self.server_url))
+            .query(&[("service", &service)])
+            .send()
+            .await
+            .map_err(Error::Network)?;
+        if !page_response.status().is_success() {
+            return Err(Error::status(&page_response));
+        }
+        store_response_cookies(cookies, &page_response);
+        let page_url = page_response.url().clone();
+        let plain_text = page_response.text().await.map_err(Error::Network)?;
+        let form = scrape::cas_login_form(&plain_text)?;
+        let action = match &form.action {
+            Some(action) => page_url.join(action).map_err(|_| Error::Layout {
+                page: "CAS login",
+                marker: "form action",
+                fragment: Some(action.clone()),
+            })?,
+            None => page_url,
+        };
+
+        let mut remote_payload = form.hidden_fields;
+        remote_payload.push((String::from("username"), username));
+        remote_payload.push((String::from("password"), password));
+        let mut request = requester.post(action.clone()).form(&remote_payload);
+        if let Some(action_cookies) = cookies.cookies(&action) {
+            request = request.header(COOKIE, action_cookies);
+        }
+        let login_response = request.send().await.map_err(Error::Network)?;
+
+        // Refused credentials render the form again, with a 401 on recent
+        // CAS versions
+        let status = login_response.status();
+        if status.is_success() || status == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(Error::Unauthorized);
+        }
+        if !status.is_redirection() {
+            return Err(Error::status(&login_response));
+        }
+        store_response_cookies(cookies, &login_response);
+        let ticket_url = redirect_location(&login_response)
+            .filter(|url| url.query_pairs().any(|(key, _)| key == "ticket"))
+            .ok_or_else(|| Error::Layout {
+                page: "CAS login",
+                marker: "service ticket redirect",
+                fragment: location(&login_response).map(str::to_owned),
+            })?;
+
+        let mut request = requester.get(ticket_url.clone());
+        if let Some(service_cookies) = cookies.cookies(&ticket_url) {
+            request = request.header(COOKIE, service_cookies);
+        }
+        let service_response = request.send().await.map_err(Error::Network)?;
+        if !service_response.status().is_redirection() {
+            return Err(Error::status(&service_response));
+        }
+        // Celcat sends a refused ticket back to the CAS server
+        if redirect_location(&service_response)
+            .is_some_and(|url| url.as_str().starts_with(&self.server_url))
+        {
+            return Err(Error::Unauthorized);
+        }
+        store_response_cookies(cookies, &service_response);
+        Ok(())
+    }
+}
+
+impl AuthStrategy for CasLogin {
+    fn login<'a>(
+        &'a self,
+        requester: &'a reqwest::Client,
+        base_url: &'a str,
+        username: String,
+        password: String,
+        cookies: &'a dyn CookieStore,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(self.logon(requester, base_url, username, password, cookies))
+    }
+}
+
+fn location(response: &reqwest::Response) -> Option<&str> {
+    response.headers().get(LOCATION)?.to_str().ok()
+}
+
+/// Where a redirect leads, resolved against the URL of `response`.
+fn redirect_location(response: &reqwest::Response) -> Option<Url> {
+    response.url().join(location(response)?).ok()
+}
+
+/// Logs in with `strategy` and returns the cookies of the new session.
+pub async fn login(
+    requester: &reqwest::Client,
+    base_url: &str,
+    strategy: &dyn AuthStrategy,
+    username: String,
+    password: String,
+) -> Result<SessionCookies, Error> {
+    let cookies = Jar::default();
+    strategy
+        .login(requester, base_url, username, password, &cookies)
+        .await?;
+    // Only the cookies scoped to the calendar, not to the login pages
+    let url = Url::parse(&format!("{base_url}/")).map_err(|err| Error::BaseUrl {
+        url: base_url.to_owned(),
+        reason: err.to_string(),
+    })?;
+    Ok(SessionCookies::from_store(&cookies, &url))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct InfosResponse {
     pub federation_id: String,
     pub display_name: String,
 }
 
-pub async fn get_infos(requester: &reqwest::Client, token: String) -> Result<InfosResponse, Error> {
-    let federation_id_response = requester
-        .get("https://services-web.cyu.fr/calendar")
-        .header("Cookie", token.clone())
-        .send()
+pub async fn get_infos(
+    requester: &reqwest::Client,
+    base_url: &str,
+    token: &SessionCookies,
+) -> Result<InfosResponse, Error> {
+    let federation_id_response = token
+        .send(requester.get(base_url))
         .await
-        .map_err(|_| Error::Remote)?;
+        .map_err(Error::Network)?;
+
+    if federation_id_response.status().is_redirection() {
+        return Err(Error::Unauthorized);
+    }
+    if !federation_id_response.status().is_success() {
+        return Err(Error::status(&federation_id_response));
+    }
 
     let plain_text = federation_id_response
         .text()
         .await
-        .map_err(|_| Error::Remote)?;
-
-    static FEDERATION_ID_REGEX: Lazy<Regex> =
-        Lazy::new(|| Regex::new(r#"var federationIdStr = '(.*?)';"#).unwrap());
-    let federation_id = FEDERATION_ID_REGEX
-        .captures(&plain_text)
-        .and_then(|captures| captures.get(1))
-        .map(|federation_id| federation_id.as_str().to_string())
-        .ok_or(Error::Unauthorized)?;
-
-    let name_response = requester
-        .post("https://services-web.cyu.fr/calendar/Home/LoadDisplayNames")
-        .form(&json!({
-            "federationIds[]": federation_id,
-            "resType": 104
-        }))
-        .header("Content-Type", "application/x-www-form-urlencoded")
-        .header("Cookie", token)
-        .send()
-        .await
-        .map_err(|_| Error::Remote)?;
+        .map_err(Error::Network)?;
+
+    let federation_id = match scrape::federation_id(&plain_text) {
+        Ok(federation_id) => federation_id,
+        Err(_) if scrape::is_login_page(&plain_text) => return Err(Error::Unauthorized),
+        Err(error) => return Err(error),
+    };
 
-    let infos = name_response
-        .json::<Vec<InfosResponse>>()
+    let name_response = token
+        .send(
+            requester
+                .post(format!("{base_url}/Home/LoadDisplayNames"))
+                .form(&json!({
+                    "federationIds[]": federation_id,
+                    "resType": ResourceType::Student
+                }))
+                .header("Content-Type", "application/x-www-form-urlencoded"),
+        )
         .await
-        .map_err(|_| Error::Remote)
-        .and_then(|infos| infos.into_iter().next().ok_or(Error::Remote))?;
+        .map_err(Error::Network)?;
+
+    if name_response.status().is_redirection() {
+        return Err(Error::Unauthorized);
+    }
+    if !name_response.status().is_success() {
+        return Err(Error::status(&name_response));
+    }
+
+    let body = name_response.text().await.map_err(Error::Network)?;
+    let infos = serde_json::from_str::<Vec<InfosResponse>>(&body)
+        .map_err(|source| Error::Json {
+            endpoint: "Home/LoadDisplayNames",
+            source,
+        })?
+        .into_iter()
+        .next()
+        .ok_or(Error::Layout {
+            page: "Home/LoadDisplayNames",
+            marker: "display name entry",
+            fragment: None,
+        })?;
 
     Ok(infos)
 }
+
+/// Weeks of events the groups of a student are read from.
+const PROFILE_WEEKS: u64 = 6;
+
+/// What the calendar tells about the logged in student, beyond their
+/// [`InfosResponse`].
+///
+/// Celcat has no page listing the groups of a student, so they are read
+/// from the events of the coming weeks.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct StudentProfile {
+    pub federation_id: String,
+    pub display_name: String,
+    /// Groups of the student's events, the most frequent first.
+    pub groups: Vec<String>,
+    /// Programme the student follows, e.g. `L3 INFORMATIQUE`: the group of
+    /// most of their events, since lectures gather the whole programme.
+    pub programme: Option<String>,
+    /// Department giving most of their events, e.g. `CY Tech`.
+    pub department: Option<String>,
+    /// First and last days the calendar can be browsed.
+    pub date_extents: (CyuDate, CyuDate),
+}
+
+impl StudentProfile {
+    fn new(
+        infos: InfosResponse,
+        date_extents: (CyuDate, CyuDate),
+        events: &[GetCalendarResponseElement],
+    ) -> Self {
+        let mut groups: HashMap<String, usize> = HashMap::new();
+        let mut departments: HashMap<&str, usize> = HashMap::new();
+        for event in events {
+            let mut details = event.details();
+            details.groups.sort();
+            details.groups.dedup();
+            for group in details.groups {
+                *groups.entry(group).or_default() += 1;
+            }
+            if !event.department().is_empty() {
+                *departments.entry(event.department()).or_default() += 1;
+            }
+        }
+
+        // Shorter names first on ties, the whole programme before its groups
+        let mut groups = groups.into_iter().collect::<Vec<_>>();
+        groups.sort_by(|(a, a_count), (b, b_count)| {
+            (b_count, a.len(), a).cmp(&(a_count, b.len(), b))
+        });
+        let groups = groups
+            .into_iter()
+            .map(|(group, _)| group)
+            .collect::<Vec<_>>();
+        let department = departments
+            .into_iter()
+            .max_by(|(a, a_count), (b, b_count)| (a_count, b).cmp(&(b_count, a)))
+            .map(|(department, _)| department.to_owned());
+        Self {
+            federation_id: infos.federation_id,
+            display_name: infos.display_name,
+            programme: groups.first().cloned(),
+            groups,
+            department,
+            date_extents,
+        }
+    }
+}
+
+/// First and last days of `years`, the date extents they were split from.
+fn date_extents(years: &[AcademicYear]) -> Result<(CyuDate, CyuDate), Error> {
+    match (years.first(), years.last()) {
+        (Some(first), Some(last)) => Ok((first.start.clone(), last.end.clone())),
+        _ => Err(Error::NoAcademicYear),
+    }
+}
+
+/// Range of the calendar the profile is read from: the weeks from `date`,
+/// or the last weeks of the calendar once it is over.
+fn profile_range((first, last): &(CyuDate, CyuDate), date: &CyuDate) -> (CyuDate, CyuDate) {
+    let span = Days::new(PROFILE_WEEKS * 7 - 1);
+    let start = if date > last {
+        last.checked_sub_days(span)
+            .map(CyuDate::from)
+            .unwrap_or_default()
+    } else {
+        date.clone()
+    };
+    let start = start.max(first.clone());
+    let end = start
+        .checked_add_days(span)
+        .map(CyuDate::from)
+        .unwrap_or_default()
+        .min(last.clone());
+    (start, end)
+}
+
+fn profile_query(
+    id: String,
+    token: SessionCookies,
+    (start, end): (CyuDate, CyuDate),
+) -> GetRangeQuery {
+    GetRangeQuery {
+        id,
+        resource_type: ResourceType::Student,
+        token,
+        start,
+        end,
+        color_by: ColorBy::EventCategory,
+        options: RangeOptions::default(),
+    }
+}
+
+/// Infos of the logged in student, with their groups and the extents of
+/// the calendar, see [`StudentProfile`]. The groups are read from the weeks
+/// from `date`, today in `timezone` when `None`.
+pub async fn get_profile(
+    requester: &reqwest::Client,
+    base_url: &str,
+    timezone: chrono_tz::Tz,
+    token: &SessionCookies,
+    date: Option<CyuDate>,
+) -> Result<StudentProfile, Error> {
+    get_profile_with(
+        &Direct {
+            requester,
+            base_url,
+            timezone,
+        },
+        token,
+        date,
+    )
+    .await
+}
+
+pub(crate) async fn get_profile_with(
+    backend: &dyn Backend,
+    token: &SessionCookies,
+    date: Option<CyuDate>,
+) -> Result<StudentProfile, Error> {
+    let infos = backend.get_infos(token).await?;
+    let date = date.unwrap_or_else(|| backend.today());
+    let years = backend
+        .get_academic_years(GetLimitsQuery {
+            id: &infos.federation_id,
+            resource_type: ResourceType::Student,
+            token,
+            date: Some(date.clone()),
+        })
+        .await?;
+    let extents = date_extents(&years)?;
+    let range = profile_range(&extents, &date);
+    let query = profile_query(infos.federation_id.clone(), token.clone(), range);
+    let events = backend.get_range(query).await?.events;
+    Ok(StudentProfile::new(infos, extents, &events))
+}
@@ -0,0 +1,80 @@
+use crate::auth::{self, InfosResponse};
+use crate::calendar::{
+    self, GetCalendarQuery, GetCalendarResponse, GetLimitsQuery, GetRangeQuery, GetRangeResponse,
+};
+use crate::cookies::SessionCookies;
+use crate::errors::Error;
+use crate::utils::{AcademicYear, CyuDate};
+use futures::future::BoxFuture;
+
+/// The requests the composite ones are made of, e.g. [`calendar::get_all`]
+/// being the limits of the year and then its range, or
+/// [`auth::get_profile`] starting with the infos of the student.
+///
+/// Implemented by [`Direct`] for the free functions, and by
+/// [`crate::Fetcher`] which puts its cache in between, so that each
+/// composite request is written once.
+pub(crate) trait Backend: Sync {
+    /// Today, in the timezone of the instance.
+    fn today(&self) -> CyuDate;
+
+    fn get_infos<'a>(
+        &'a self,
+        token: &'a SessionCookies,
+    ) -> BoxFuture<'a, Result<InfosResponse, Error>>;
+
+    fn get_calendar(
+        &self,
+        query: GetCalendarQuery,
+    ) -> BoxFuture<'_, Result<GetCalendarResponse, Error>>;
+
+    fn get_academic_years<'a>(
+        &'a self,
+        query: GetLimitsQuery<'a>,
+    ) -> BoxFuture<'a, Result<Vec<AcademicYear>, Error>>;
+
+    fn get_range(&self, query: GetRangeQuery) -> BoxFuture<'_, Result<GetRangeResponse, Error>>;
+}
+
+/// Sends every request to CYU.
+pub(crate) struct Direct<'a> {
+    pub requester: &'a reqwest::Client,
+    pub base_url: &'a str,
+    pub timezone: chrono_tz::Tz,
+}
+
+impl Backend for Direct<'_> {
+    fn today(&self) -> CyuDate {
+        CyuDate::today_in(self.timezone)
+    }
+
+    fn get_infos<'a>(
+        &'a self,
+        token: &'a SessionCookies,
+    ) -> BoxFuture<'a, Result<InfosResponse, Error>> {
+        Box::pin(auth::get_infos(self.requester, self.base_url, token))
+    }
+
+    fn get_calendar(
+        &self,
+        query: GetCalendarQuery,
+    ) -> BoxFuture<'_, Result<GetCalendarResponse, Error>> {
+        Box::pin(calendar::get_calendar(self.requester, self.base_url, query))
+    }
+
+    fn get_academic_years<'a>(
+        &'a self,
+        query: GetLimitsQuery<'a>,
+    ) -> BoxFuture<'a, Result<Vec<AcademicYear>, Error>> {
+        Box::pin(calendar::get_academic_years(
+            self.requester,
+            self.base_url,
+            self.timezone,
+            query,
+        ))
+    }
+
+    fn get_range(&self, query: GetRangeQuery) -> BoxFuture<'_, Result<GetRangeResponse, Error>> {
+        Box::pin(calendar::get_range(self.requester, self.base_url, query))
+    }
+}
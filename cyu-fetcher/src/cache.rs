@@ -0,0 +1,234 @@
+use crate::calendar::{ColorBy, GetCalendarResponse, GetRangeResponse, ResourceType};
+use crate::cookies::SessionCookies;
+use crate::utils::{AcademicYear, CyuDate};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long [`crate::Fetcher`] keeps what CYU answered, see
+/// [`crate::FetcherBuilder::cache`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheConfig {
+    /// Age under which an entry is served without asking CYU.
+    pub ttl: Duration,
+    /// Extra time during which an expired entry is still served, while a
+    /// fresh copy is fetched in the background.
+    pub stale_while_revalidate: Duration,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(5 * 60),
+            stale_while_revalidate: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct CalendarKey {
+    pub id: String,
+    pub resource_type: ResourceType,
+    pub start: CyuDate,
+    pub end: CyuDate,
+    pub color_by: ColorBy,
+}
+
+pub(crate) enum Lookup<V> {
+    Hit(V),
+    /// The entry is stale and nobody is refreshing it yet: the caller
+    /// should do it.
+    Revalidate(V),
+    Miss,
+}
+
+struct Entry<V> {
+    value: V,
+    /// Federation id of the session the value was fetched with. Sessions of
+    /// other users never see it, so a forged federation id cannot read
+    /// someone else's calendar.
+    owner: String,
+    fetched_at: Instant,
+    revalidating: bool,
+}
+
+pub(crate) struct Store<K, V> {
+    entries: Mutex<HashMap<K, Entry<V>>>,
+}
+
+impl<K, V> Default for Store<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<K: Eq + Hash, V: Clone> Store<K, V> {
+    pub fn get(&self, config: &CacheConfig, key: &K, owner: &str) -> Lookup<V> {
+        let mut entries = self.entries.lock().unwrap();
+        let Some(entry) = entries.get_mut(key) else {
+            return Lookup::Miss;
+        };
+        if entry.owner != owner {
+            return Lookup::Miss;
+        }
+
+        let age = entry.fetched_at.elapsed();
+        if age < config.ttl {
+            Lookup::Hit(entry.value.clone())
+        } else if age < config.ttl + config.stale_while_revalidate {
+            if entry.revalidating {
+                Lookup::Hit(entry.value.clone())
+            } else {
+                entry.revalidating = true;
+                Lookup::Revalidate(entry.value.clone())
+            }
+        } else {
+            entries.remove(key);
+            Lookup::Miss
+        }
+    }
+
+    pub fn insert(&self, config: &CacheConfig, key: K, owner: String, value: V) {
+        let mut entries = self.entries.lock().unwrap();
+        let lifetime = config.ttl + config.stale_while_revalidate;
+        entries.retain(|_, entry| entry.fetched_at.elapsed() < lifetime);
+        entries.insert(
+            key,
+            Entry {
+                value,
+                owner,
+                fetched_at: Instant::now(),
+                revalidating: false,
+            },
+        );
+    }
+
+    /// An expired session drops the entry so that the next call reports it,
+    /// anything else lets the next call try to refresh it again.
+    pub fn fetch_failed(&self, key: &K, session_expired: bool) {
+        let mut entries = self.entries.lock().unwrap();
+        if session_expired {
+            entries.remove(key);
+        } else if let Some(entry) = entries.get_mut(key) {
+            entry.revalidating = false;
+        }
+    }
+
+    /// Drops the entries fetched with the sessions of `owner`, whatever
+    /// resource they are about.
+    pub fn remove_owner(&self, owner: &str) {
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|_, entry| entry.owner != owner);
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+/// A response, and what of it is kept in a [`Store`].
+pub(crate) trait Cacheable<V> {
+    fn from_cached(value: V) -> Self;
+    /// `None` when the response must not be cached, e.g. it is partial.
+    fn to_cached(&self) -> Option<V>;
+}
+
+impl<V: Clone> Cacheable<V> for V {
+    fn from_cached(value: V) -> Self {
+        value
+    }
+
+    fn to_cached(&self) -> Option<V> {
+        Some(self.clone())
+    }
+}
+
+impl Cacheable<GetCalendarResponse> for GetRangeResponse {
+    fn from_cached(events: GetCalendarResponse) -> Self {
+        Self {
+            events,
+            failures: Vec::new(),
+        }
+    }
+
+    fn to_cached(&self) -> Option<GetCalendarResponse> {
+        self.is_complete().then(|| self.events.clone())
+    }
+}
+
+/// Federation id each session was verified to belong to, so that every
+/// session of a user shares the same entries. Sessions are told apart by
+/// their cookies, so one renewed by Celcat is verified again.
+#[derive(Default)]
+pub(crate) struct Sessions {
+    owners: Mutex<HashMap<BTreeMap<String, String>, (String, Instant)>>,
+}
+
+impl Sessions {
+    pub fn owner(&self, config: &CacheConfig, token: &SessionCookies) -> Option<String> {
+        let owners = self.owners.lock().unwrap();
+        let (owner, verified_at) = owners.get(&token.snapshot())?;
+        let lifetime = config.ttl + config.stale_while_revalidate;
+        (verified_at.elapsed() < lifetime).then(|| owner.clone())
+    }
+
+    pub fn insert(&self, config: &CacheConfig, token: &SessionCookies, owner: String) {
+        let mut owners = self.owners.lock().unwrap();
+        let lifetime = config.ttl + config.stale_while_revalidate;
+        owners.retain(|_, (_, verified_at)| verified_at.elapsed() < lifetime);
+        owners.insert(token.snapshot(), (owner, Instant::now()));
+    }
+
+    pub fn remove_owner(&self, owner: &str) {
+        self.owners
+            .lock()
+            .unwrap()
+            .retain(|_, (session_owner, _)| session_owner != owner);
+    }
+
+    pub fn clear(&self) {
+        self.owners.lock().unwrap().clear();
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct FetcherCache {
+    pub config: CacheConfig,
+    pub sessions: Sessions,
+    pub calendars: Store<CalendarKey, GetCalendarResponse>,
+    /// Academic years exposed for a timetable around a date.
+    pub academic_years: Store<(String, ResourceType, CyuDate), Vec<AcademicYear>>,
+}
+
+impl FetcherCache {
+    pub fn new(config: CacheConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Drops the sessions of `federation_id` and everything they fetched.
+    pub fn invalidate(&self, federation_id: &str) {
+        self.sessions.remove_owner(federation_id);
+        self.calendars.remove_owner(federation_id);
+        self.academic_years.remove_owner(federation_id);
+    }
+
+    pub fn clear(&self) {
+        self.sessions.clear();
+        self.calendars.clear();
+        self.academic_years.clear();
+    }
+}
+
+/// Runtime on which stale entries are refreshed in the background. Without
+/// one, they are refreshed before being returned, like missing entries.
+pub(crate) fn background_runtime() -> Option<tokio::runtime::Handle> {
+    tokio::runtime::Handle::try_current().ok()
+}
@@ -0,0 +1,114 @@
+use super::GetCalendarResponseElement;
+use crate::utils::{CyuDate, CyuDateTime};
+use chrono::{Datelike as _, NaiveDateTime, NaiveTime, TimeDelta, Weekday};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy)]
+pub struct AvailabilityOptions {
+    /// Earliest time a slot may start at, every day.
+    pub day_start: NaiveTime,
+    /// Latest time a slot may end at, every day.
+    pub day_end: NaiveTime,
+    pub skip_weekends: bool,
+    /// Shorter free windows are left out.
+    pub min_duration: TimeDelta,
+}
+
+impl Default for AvailabilityOptions {
+    fn default() -> Self {
+        Self {
+            day_start: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+            day_end: NaiveTime::from_hms_opt(19, 0, 0).unwrap(),
+            skip_weekends: true,
+            min_duration: TimeDelta::hours(1),
+        }
+    }
+}
+
+/// A window where nobody has anything planned.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct FreeSlot {
+    pub start: CyuDateTime,
+    pub end: CyuDateTime,
+}
+
+impl FreeSlot {
+    pub fn duration(&self) -> TimeDelta {
+        *self.end - *self.start
+    }
+}
+
+/// How long an event without an end is taken to last.
+const DEFAULT_EVENT_DURATION: TimeDelta = TimeDelta::hours(1);
+
+/// Time taken by an event, all-day events taking their whole days.
+fn busy_interval(event: &GetCalendarResponseElement) -> (NaiveDateTime, NaiveDateTime) {
+    let start = **event.start();
+    if *event.all_day() {
+        let first = start.date().and_time(NaiveTime::MIN);
+        // The end of an all-day event is the day after its last one
+        let end = event
+            .end()
+            .as_ref()
+            .map(|end| **end)
+            .filter(|end| *end > first)
+            .unwrap_or(first + TimeDelta::days(1));
+        return (first, end);
+    }
+    let end = event
+        .end()
+        .as_ref()
+        .map(|end| **end)
+        .unwrap_or(start + DEFAULT_EVENT_DURATION);
+    (start, end.max(start))
+}
+
+/// Free windows from `start` to `end` included, given the events of
+/// everyone concerned.
+///
+/// Events of several calendars can be chained, e.g. the timetables of the
+/// members of a project group, to find the windows they all have free.
+pub fn find_free_slots<'a>(
+    events: impl IntoIterator<Item = &'a GetCalendarResponseElement>,
+    start: &CyuDate,
+    end: &CyuDate,
+    options: &AvailabilityOptions,
+) -> Vec<FreeSlot> {
+    let mut busy = events.into_iter().map(busy_interval).collect::<Vec<_>>();
+    busy.sort();
+
+    let mut slots = Vec::new();
+    for date in start.iter_days().take_while(|date| date <= &**end) {
+        let weekend = matches!(date.weekday(), Weekday::Sat | Weekday::Sun);
+        if options.skip_weekends && weekend {
+            continue;
+        }
+        let (day_start, day_end) = (
+            date.and_time(options.day_start),
+            date.and_time(options.day_end),
+        );
+
+        let long_enough =
+            |from: NaiveDateTime, to: NaiveDateTime| to > from && to - from >= options.min_duration;
+        let mut free_from = day_start;
+        let overlapping = busy
+            .iter()
+            .filter(|(busy_start, busy_end)| *busy_start < day_end && *busy_end > day_start);
+        for &(busy_start, busy_end) in overlapping {
+            if long_enough(free_from, busy_start) {
+                slots.push(FreeSlot {
+                    start: free_from.into(),
+                    end: busy_start.into(),
+                });
+            }
+            free_from = free_from.max(busy_end);
+        }
+        if long_enough(free_from, day_end) {
+            slots.push(FreeSlot {
+                start: free_from.into(),
+                end: day_end.into(),
+            });
+        }
+    }
+    slots
+}
@@ -0,0 +1,167 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::Display;
+
+/// Kind of an event, parsed from the `eventCategory` of Celcat.
+///
+/// Celcat instances are free to name their categories, so anything we do
+/// not know is kept verbatim in [`EventKind::Other`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    /// `CM`, cours magistral
+    Lecture,
+    /// `TD`, travaux dirigés
+    Tutorial,
+    /// `TP`, travaux pratiques
+    Practical,
+    Exam,
+    ContinuousAssessment,
+    Defense,
+    Meeting,
+    Holiday,
+    Other(String),
+}
+
+impl EventKind {
+    /// The code CYU uses for this kind.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Lecture => "CM",
+            Self::Tutorial => "TD",
+            Self::Practical => "TP",
+            Self::Exam => "Examen",
+            Self::ContinuousAssessment => "Contrôle continu",
+            Self::Defense => "Soutenance",
+            Self::Meeting => "Réunion",
+            Self::Holiday => "Vacances",
+            Self::Other(category) => category,
+        }
+    }
+
+    /// Human readable name, in French like the rest of CYU.
+    pub fn display_name(&self) -> &str {
+        match self {
+            Self::Lecture => "Cours magistral",
+            Self::Tutorial => "Travaux dirigés",
+            Self::Practical => "Travaux pratiques",
+            Self::Exam => "Examen",
+            Self::ContinuousAssessment => "Contrôle continu",
+            Self::Defense => "Soutenance",
+            Self::Meeting => "Réunion",
+            Self::Holiday => "Vacances",
+            Self::Other(category) => category,
+        }
+    }
+
+    /// Color used when Celcat colors events by subject rather than by category.
+    pub fn default_color(&self) -> &'static str {
+        match self {
+            Self::Lecture => "#3584e4",
+            Self::Tutorial => "#33d17a",
+            Self::Practical => "#f6d32d",
+            Self::Exam | Self::ContinuousAssessment => "#e01b24",
+            Self::Defense => "#c061cb",
+            Self::Meeting => "#986a44",
+            Self::Holiday => "#9a9996",
+            Self::Other(_) => "#77767b",
+        }
+    }
+
+    pub fn is_exam(&self) -> bool {
+        matches!(self, Self::Exam | Self::ContinuousAssessment)
+    }
+}
+
+impl From<&str> for EventKind {
+    fn from(category: &str) -> Self {
+        let normalized = category.trim().to_lowercase();
+        match normalized.as_str() {
+            "cm" | "cours" | "cours magistral" => Self::Lecture,
+            "td" | "travaux dirigés" | "travaux diriges" => Self::Tutorial,
+            "tp" | "travaux pratiques" => Self::Practical,
+            "examen" | "examens" | "exam" | "partiel" | "partiels" => Self::Exam,
+            "cc" | "contrôle continu" | "controle continu" | "contrôle" | "controle" => {
+                Self::ContinuousAssessment
+            }
+            "soutenance" | "soutenances" => Self::Defense,
+            "réunion" | "reunion" => Self::Meeting,
+            "vacances" | "férié" | "ferie" | "jour férié" | "jour ferie" => Self::Holiday,
+            _ => Self::Other(category.trim().to_owned()),
+        }
+    }
+}
+
+impl Display for EventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl Serialize for EventKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// `eventCategory` of an event, as given by Celcat and parsed.
+///
+/// Serialized as given, so that e.g. `Cours` is not turned into `CM`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EventCategory {
+    pub kind: EventKind,
+    pub raw: String,
+}
+
+impl EventCategory {
+    /// The category as given by Celcat.
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    pub fn display_name(&self) -> &str {
+        self.kind.display_name()
+    }
+
+    pub fn default_color(&self) -> &'static str {
+        self.kind.default_color()
+    }
+
+    pub fn is_exam(&self) -> bool {
+        self.kind.is_exam()
+    }
+}
+
+impl From<&str> for EventCategory {
+    fn from(category: &str) -> Self {
+        Self {
+            kind: category.into(),
+            raw: category.to_owned(),
+        }
+    }
+}
+
+impl From<String> for EventCategory {
+    fn from(category: String) -> Self {
+        Self {
+            kind: category.as_str().into(),
+            raw: category,
+        }
+    }
+}
+
+impl Display for EventCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl Serialize for EventCategory {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'a> Deserialize<'a> for EventCategory {
+    fn deserialize<D: Deserializer<'a>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
@@ -0,0 +1,160 @@
+use super::GetCalendarResponseElement;
+use crate::sites::{Location, SiteRegistry};
+use chrono::{NaiveDateTime, TimeDelta};
+use serde::{Serialize, Serializer};
+
+#[derive(Debug, Clone, Copy)]
+pub struct ConflictOptions {
+    /// Walking speed between sites, in km/h.
+    pub walking_speed: f64,
+    /// Time needed to leave a room and settle in another one, on top of the
+    /// walk itself.
+    pub changeover: TimeDelta,
+}
+
+impl Default for ConflictOptions {
+    fn default() -> Self {
+        Self {
+            walking_speed: 4.5,
+            changeover: TimeDelta::minutes(5),
+        }
+    }
+}
+
+fn serialize_minutes<S: Serializer>(duration: &TimeDelta, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_i64(duration.num_minutes())
+}
+
+/// Why two events cannot both be attended. Durations are serialized in
+/// minutes.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConflictKind {
+    /// Both events take place at the same time.
+    Overlap {
+        #[serde(serialize_with = "serialize_minutes")]
+        duration: TimeDelta,
+    },
+    /// The second event is at another site, too far to get there in time.
+    TravelTime {
+        from: String,
+        to: String,
+        #[serde(serialize_with = "serialize_minutes")]
+        needed: TimeDelta,
+        #[serde(serialize_with = "serialize_minutes")]
+        available: TimeDelta,
+    },
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Conflict {
+    /// The event starting first.
+    pub first: GetCalendarResponseElement,
+    pub second: GetCalendarResponseElement,
+    pub kind: ConflictKind,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct ConflictReport {
+    pub conflicts: Vec<Conflict>,
+}
+
+impl ConflictReport {
+    pub fn is_empty(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+
+    /// Conflicts `id` takes part in, to highlight an event.
+    pub fn involving<'a>(&'a self, id: &'a str) -> impl Iterator<Item = &'a Conflict> {
+        self.conflicts
+            .iter()
+            .filter(move |conflict| conflict.first.id() == id || conflict.second.id() == id)
+    }
+}
+
+/// Great-circle distance between two `[latitude, longitude]`, in km.
+fn distance(from: &[f64; 2], to: &[f64; 2]) -> f64 {
+    const EARTH_RADIUS: f64 = 6371.0;
+    let (lat1, lat2) = (from[0].to_radians(), to[0].to_radians());
+    let (dlat, dlon) = (lat2 - lat1, (to[1] - from[1]).to_radians());
+    let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS * a.sqrt().asin()
+}
+
+/// Time needed to go from `from` to `to`, `None` when they are at the same
+/// place.
+fn travel_time(from: &Location, to: &Location, options: &ConflictOptions) -> Option<TimeDelta> {
+    let (from, to) = (from.coordinates(), to.coordinates());
+    if from == to {
+        return None;
+    }
+    let hours = distance(&from, &to) / options.walking_speed;
+    let walk = TimeDelta::seconds((hours * 3600.0).ceil() as i64);
+    Some(walk + options.changeover)
+}
+
+fn end(event: &GetCalendarResponseElement) -> NaiveDateTime {
+    event.end().as_ref().map_or(**event.start(), |end| **end)
+}
+
+/// Finds overlapping events and back-to-back events at sites too far apart,
+/// sorted by the start of the first event.
+///
+/// All-day events, such as holidays, never conflict. Travel time is only
+/// checked between events `sites` can locate.
+pub fn find_conflicts(
+    events: &[GetCalendarResponseElement],
+    sites: &SiteRegistry,
+    options: &ConflictOptions,
+) -> ConflictReport {
+    let mut events = events
+        .iter()
+        .filter(|event| !event.all_day())
+        .collect::<Vec<_>>();
+    events.sort_by(|a, b| (a.start(), a.id()).cmp(&(b.start(), b.id())));
+
+    let mut conflicts = Vec::new();
+    for (index, first) in events.iter().enumerate() {
+        let first_end = end(first);
+        // Only the events starting right after `first` need travelling from it
+        let mut next_start = None;
+        for second in &events[index + 1..] {
+            let second_start = **second.start();
+            if second_start < first_end {
+                conflicts.push(Conflict {
+                    first: (*first).clone(),
+                    second: (*second).clone(),
+                    kind: ConflictKind::Overlap {
+                        duration: first_end.min(end(second)) - second_start,
+                    },
+                });
+                continue;
+            }
+            if next_start.is_some_and(|next_start| second_start > next_start) {
+                break;
+            }
+            next_start = Some(second_start);
+
+            let locate = |event| sites.locate(event).ok().flatten();
+            let (Some(from), Some(to)) = (locate(first), locate(second)) else {
+                continue;
+            };
+            let available = second_start - first_end;
+            if let Some(needed) = travel_time(&from, &to, options) {
+                if needed > available {
+                    conflicts.push(Conflict {
+                        first: (*first).clone(),
+                        second: (*second).clone(),
+                        kind: ConflictKind::TravelTime {
+                            from: from.name(),
+                            to: to.name(),
+                            needed,
+                            available,
+                        },
+                    });
+                }
+            }
+        }
+    }
+    ConflictReport { conflicts }
+}
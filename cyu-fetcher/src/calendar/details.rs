@@ -0,0 +1,142 @@
+use super::{EventCategory, EventKind};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+
+/// The pieces of information Celcat packs into an event description.
+///
+/// Celcat only gives us free text, one item per line, and the set of lines
+/// depends on the event. Lines are classified by shape rather than by
+/// position, using the structured fields of the event as hints.
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventDetails {
+    pub category: Option<EventCategory>,
+    pub module: Option<String>,
+    pub rooms: Vec<String>,
+    pub teachers: Vec<String>,
+    pub groups: Vec<String>,
+    pub remarks: Vec<String>,
+}
+
+/// Structured fields of an event that help classifying description lines.
+#[derive(Debug, Clone, Copy)]
+pub struct DescriptionHints<'a> {
+    pub category: &'a EventCategory,
+    pub sites: &'a [String],
+    pub modules: &'a [String],
+    pub department: &'a str,
+    pub faculty: Option<&'a str>,
+}
+
+static ROOM_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?x)
+        ^(?i:salle|amphi|amphithéâtre|amphitheatre|labo|gymnase|visio|distanciel)\b
+        | \[[^\]]*[0-9][^\]]*\]$
+        | ^[A-Z]{1,5}(-[A-Z0-9]+)*[-\ ]?[0-9]{1,4}[A-Z]?$",
+    )
+    .unwrap()
+});
+
+static GROUP_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?x)
+        ^(L[1-3]|M[1-2]|LP|BUT[1-3]?|DUT|ING[1-5]?|CPI[1-2]?|PREPA|DU)\b
+        | \b(GR|Gr|GROUPE|Groupe|TD|TP)\ ?[0-9A-Z]{1,3}$
+        | \b(FI|FA|ALT|S[0-9]{1,2})$",
+    )
+    .unwrap()
+});
+
+static TEACHER_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^[A-ZÀ-ÖØ-Þ][A-ZÀ-ÖØ-Þ'\- ]+ [A-ZÀ-ÖØ-Þ][a-zß-öø-ÿ]+([\- ][A-ZÀ-ÖØ-Þ][a-zß-öø-ÿ]+)*$",
+    )
+    .unwrap()
+});
+
+fn is_room(line: &str) -> bool {
+    ROOM_REGEX.is_match(line)
+}
+
+fn is_group(line: &str) -> bool {
+    GROUP_REGEX.is_match(line)
+}
+
+fn is_teacher(line: &str) -> bool {
+    TEACHER_REGEX.is_match(line)
+}
+
+/// Removes the category when Celcat repeats it after the module name,
+/// as in `Analyse CM` or `Analyse [TD]`.
+fn strip_category(module: &str, category: &EventCategory) -> String {
+    let category = category.as_str();
+    if category.is_empty() {
+        return module.to_owned();
+    }
+    let stripped = module
+        .strip_suffix(&format!("[{category}]"))
+        .or_else(|| module.strip_suffix(category))
+        .filter(|rest| rest.is_empty() || rest.ends_with(' '))
+        .unwrap_or(module)
+        .trim();
+    if stripped.is_empty() {
+        module.to_owned()
+    } else {
+        stripped.to_owned()
+    }
+}
+
+impl EventDetails {
+    /// Parses a description whose line breaks were already normalized,
+    /// as returned by [`super::GetCalendarResponseElement::description`].
+    pub fn parse(description: &str, hints: DescriptionHints<'_>) -> Self {
+        let eq = |a: &str, b: &str| a.eq_ignore_ascii_case(b);
+        let mut details = Self {
+            category: Some(hints.category.clone()).filter(|category| !category.as_str().is_empty()),
+            ..Default::default()
+        };
+        let mut unknown = Vec::new();
+
+        let lines = description
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty());
+        for (index, line) in lines.enumerate() {
+            if index == 0 && EventKind::from(line) == hints.category.kind {
+                continue;
+            }
+            let is_context = hints.sites.iter().any(|site| eq(line, site))
+                || eq(line, hints.department)
+                || hints.faculty.is_some_and(|faculty| eq(line, faculty));
+            if is_context {
+                continue;
+            }
+            let is_module = hints
+                .modules
+                .iter()
+                .filter(|module| !module.is_empty())
+                .any(|module| line.to_lowercase().starts_with(&module.to_lowercase()));
+            if details.module.is_none() && is_module {
+                details.module = Some(strip_category(line, hints.category));
+            } else if is_group(line) {
+                details.groups.push(line.to_owned());
+            } else if is_room(line) {
+                details.rooms.push(line.to_owned());
+            } else if is_teacher(line) {
+                details.teachers.push(line.to_owned());
+            } else {
+                unknown.push(line);
+            }
+        }
+
+        let mut unknown = unknown.into_iter();
+        if details.module.is_none() {
+            details.module = unknown
+                .next()
+                .map(|module| strip_category(module, hints.category));
+        }
+        details.remarks = unknown.map(ToOwned::to_owned).collect();
+        details
+    }
+}
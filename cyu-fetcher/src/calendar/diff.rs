@@ -0,0 +1,261 @@
+use super::{EventCategory, EventDetails, EventKind, GetCalendarResponseElement};
+use crate::utils::CyuDateTime;
+use chrono::TimeDelta;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy)]
+pub struct DiffOptions {
+    /// How far apart two events with different ids may start and still be
+    /// matched as the same one, `None` to only match by id.
+    pub fuzzy_window: Option<TimeDelta>,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            fuzzy_window: Some(TimeDelta::days(7)),
+        }
+    }
+}
+
+/// When an event takes place.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct TimeSlot {
+    pub start: CyuDateTime,
+    pub end: Option<CyuDateTime>,
+    pub all_day: bool,
+}
+
+impl TimeSlot {
+    fn of(event: &GetCalendarResponseElement) -> Self {
+        Self {
+            start: event.start().clone(),
+            end: event.end().clone(),
+            all_day: *event.all_day(),
+        }
+    }
+}
+
+/// One aspect of an event that changed between two snapshots.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EventChange {
+    Rescheduled {
+        before: TimeSlot,
+        after: TimeSlot,
+    },
+    RoomChanged {
+        before: Vec<String>,
+        after: Vec<String>,
+    },
+    CategoryChanged {
+        before: EventCategory,
+        after: EventCategory,
+    },
+    /// The description changed for another reason than its rooms.
+    DescriptionChanged {
+        before: String,
+        after: String,
+    },
+}
+
+/// How the two versions of a modified event were paired.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchedBy {
+    Id,
+    /// Celcat recreated the event under a new id, it was recognized by its
+    /// module and category.
+    Similarity,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ModifiedEvent {
+    pub before: GetCalendarResponseElement,
+    pub after: GetCalendarResponseElement,
+    pub matched_by: MatchedBy,
+    pub changes: Vec<EventChange>,
+}
+
+/// What changed between two snapshots of a calendar.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct CalendarDiff {
+    pub added: Vec<GetCalendarResponseElement>,
+    pub removed: Vec<GetCalendarResponseElement>,
+    pub modified: Vec<ModifiedEvent>,
+}
+
+impl CalendarDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+
+    /// Modified events that moved to another time or room, the changes a
+    /// student is most likely to miss.
+    pub fn moved(&self) -> impl Iterator<Item = &ModifiedEvent> {
+        self.modified.iter().filter(|modified| {
+            modified.changes.iter().any(|change| {
+                matches!(
+                    change,
+                    EventChange::Rescheduled { .. } | EventChange::RoomChanged { .. }
+                )
+            })
+        })
+    }
+}
+
+/// Description lines other than rooms, which are reported on their own.
+fn description_without_rooms(event: &GetCalendarResponseElement, details: &EventDetails) -> String {
+    event
+        .description()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !details.rooms.iter().any(|room| room == line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn changes(
+    before: &GetCalendarResponseElement,
+    after: &GetCalendarResponseElement,
+) -> Vec<EventChange> {
+    let mut changes = Vec::new();
+    let (slot_before, slot_after) = (TimeSlot::of(before), TimeSlot::of(after));
+    if slot_before != slot_after {
+        changes.push(EventChange::Rescheduled {
+            before: slot_before,
+            after: slot_after,
+        });
+    }
+
+    let (details_before, details_after) = (before.details(), after.details());
+    if details_before.rooms != details_after.rooms {
+        changes.push(EventChange::RoomChanged {
+            before: details_before.rooms.clone(),
+            after: details_after.rooms.clone(),
+        });
+    }
+    if before.event_category().kind != after.event_category().kind {
+        changes.push(EventChange::CategoryChanged {
+            before: before.event_category().clone(),
+            after: after.event_category().clone(),
+        });
+    }
+
+    let description_before = description_without_rooms(before, &details_before);
+    let description_after = description_without_rooms(after, &details_after);
+    if description_before != description_after {
+        changes.push(EventChange::DescriptionChanged {
+            before: before.description(),
+            after: after.description(),
+        });
+    }
+    changes
+}
+
+/// What identifies an event regardless of its id: its module, or its whole
+/// description when the module is unknown, and its category.
+fn similarity_key(event: &GetCalendarResponseElement) -> (String, EventKind) {
+    let details = event.details();
+    let subject = details
+        .module
+        .clone()
+        .or_else(|| event.modules().as_ref()?.first().cloned())
+        .unwrap_or_else(|| description_without_rooms(event, &details));
+    (subject.to_lowercase(), event.event_category().kind.clone())
+}
+
+/// Compares two snapshots of the same calendar.
+///
+/// Events are paired by id first. Among the remaining ones, an event removed
+/// and an event added with the same module and category, starting at most
+/// [`DiffOptions::fuzzy_window`] apart, are taken for the same event, the
+/// closest ones being paired first.
+pub fn diff_calendars(
+    before: &[GetCalendarResponseElement],
+    after: &[GetCalendarResponseElement],
+    options: DiffOptions,
+) -> CalendarDiff {
+    let after_ids = after.iter().map(|event| event.id()).collect::<HashSet<_>>();
+    let before_by_id = before
+        .iter()
+        .map(|event| (event.id(), event))
+        .collect::<HashMap<_, _>>();
+
+    let mut diff = CalendarDiff::default();
+    let mut added = Vec::new();
+    for event in after {
+        match before_by_id.get(event.id()) {
+            Some(previous) => {
+                let changes = changes(previous, event);
+                if !changes.is_empty() {
+                    diff.modified.push(ModifiedEvent {
+                        before: (*previous).clone(),
+                        after: event.clone(),
+                        matched_by: MatchedBy::Id,
+                        changes,
+                    });
+                }
+            }
+            None => added.push(event),
+        }
+    }
+    let removed = before
+        .iter()
+        .filter(|event| !after_ids.contains(event.id()))
+        .collect::<Vec<_>>();
+
+    let mut pairs = Vec::new();
+    if let Some(window) = options.fuzzy_window {
+        let added_keys = added
+            .iter()
+            .map(|event| similarity_key(event))
+            .collect::<Vec<_>>();
+        for (i, old) in removed.iter().enumerate() {
+            let key = similarity_key(old);
+            for (j, new) in added.iter().enumerate() {
+                let distance = (**new.start() - **old.start()).abs();
+                if added_keys[j] == key && distance <= window {
+                    pairs.push((distance, i, j));
+                }
+            }
+        }
+        pairs.sort();
+    }
+
+    let (mut paired_removed, mut paired_added) = (HashSet::new(), HashSet::new());
+    for (_, i, j) in pairs {
+        if paired_removed.contains(&i) || paired_added.contains(&j) {
+            continue;
+        }
+        paired_removed.insert(i);
+        paired_added.insert(j);
+        // An event recreated as it was only changed id, which nobody cares about
+        let changes = changes(removed[i], added[j]);
+        if !changes.is_empty() {
+            diff.modified.push(ModifiedEvent {
+                before: removed[i].clone(),
+                after: added[j].clone(),
+                matched_by: MatchedBy::Similarity,
+                changes,
+            });
+        }
+    }
+
+    diff.added = added
+        .into_iter()
+        .enumerate()
+        .filter(|(j, _)| !paired_added.contains(j))
+        .map(|(_, event)| event.clone())
+        .collect();
+    diff.removed = removed
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| !paired_removed.contains(i))
+        .map(|(_, event)| event.clone())
+        .collect();
+    diff.modified
+        .sort_by(|a, b| (a.after.start(), a.after.id()).cmp(&(b.after.start(), b.after.id())));
+    diff
+}
@@ -0,0 +1,129 @@
+use super::{
+    CalendarView, ColorBy, GetCalendarQuery, GetCalendarResponse, GetCalendarResponseElement,
+    ResourceType,
+};
+use crate::backend::{Backend, Direct};
+use crate::cookies::SessionCookies;
+use crate::errors::Error;
+use crate::utils::CyuDate;
+use futures::future;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A timetable taking part in a merged calendar.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CalendarSource {
+    pub id: String,
+    pub resource_type: ResourceType,
+}
+
+impl CalendarSource {
+    pub fn new(resource_type: ResourceType, id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            resource_type,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct GetMergedCalendarQuery {
+    /// Timetables to merge, e.g. a main group and its option groups.
+    pub sources: Vec<CalendarSource>,
+    pub token: SessionCookies,
+    pub start: CyuDate,
+    pub end: CyuDate,
+    pub view: CalendarView,
+    pub color_by: ColorBy,
+}
+
+impl GetMergedCalendarQuery {
+    /// One query per source, in the order of `sources`.
+    fn queries(&self) -> impl Iterator<Item = (CalendarSource, GetCalendarQuery)> + '_ {
+        self.sources.iter().map(|source| {
+            let query = GetCalendarQuery {
+                id: source.id.clone(),
+                resource_type: source.resource_type,
+                token: self.token.clone(),
+                start: self.start.clone(),
+                end: self.end.clone(),
+                view: self.view,
+                color_by: self.color_by,
+            };
+            (source.clone(), query)
+        })
+    }
+}
+
+/// An event of a merged calendar, with the timetables it was found in.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MergedEvent {
+    #[serde(flatten)]
+    pub event: GetCalendarResponseElement,
+    pub sources: Vec<CalendarSource>,
+}
+
+pub type GetMergedCalendarResponse = Vec<MergedEvent>;
+
+/// Merges the timetables of several sources, sorted by start.
+///
+/// Celcat gives an event the same id in every timetable it appears in, so a
+/// course shared by two groups is kept once, tagged with both groups.
+pub fn merge_calendars(
+    calendars: impl IntoIterator<Item = (CalendarSource, GetCalendarResponse)>,
+) -> GetMergedCalendarResponse {
+    let mut merged: Vec<MergedEvent> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for (source, events) in calendars {
+        for event in events {
+            match positions.get(event.id()) {
+                Some(&position) => {
+                    let sources = &mut merged[position].sources;
+                    if !sources.contains(&source) {
+                        sources.push(source.clone());
+                    }
+                }
+                None => {
+                    positions.insert(event.id().clone(), merged.len());
+                    merged.push(MergedEvent {
+                        event,
+                        sources: vec![source.clone()],
+                    });
+                }
+            }
+        }
+    }
+    merged.sort_by(|a, b| (a.event.start(), a.event.id()).cmp(&(b.event.start(), b.event.id())));
+    merged
+}
+
+/// Fetches every source concurrently and merges them, see [`merge_calendars`].
+/// Fails if any of the sources does.
+pub async fn get_merged_calendar(
+    requester: &reqwest::Client,
+    base_url: &str,
+    timezone: chrono_tz::Tz,
+    query: GetMergedCalendarQuery,
+) -> Result<GetMergedCalendarResponse, Error> {
+    get_merged_calendar_with(
+        &Direct {
+            requester,
+            base_url,
+            timezone,
+        },
+        query,
+    )
+    .await
+}
+
+pub(crate) async fn get_merged_calendar_with(
+    backend: &dyn Backend,
+    query: GetMergedCalendarQuery,
+) -> Result<GetMergedCalendarResponse, Error> {
+    let calendars = future::try_join_all(query.queries().map(|(source, query)| async move {
+        let events = backend.get_calendar(query).await?;
+        Ok::<_, Error>((source, events))
+    }))
+    .await?;
+    Ok(merge_calendars(calendars))
+}
@@ -0,0 +1,353 @@
+use crate::backend::{Backend, Direct};
+use crate::cookies::SessionCookies;
+use crate::errors::Error;
+use crate::scrape;
+use crate::utils::{AcademicYear, CyuDate, CyuDateTime};
+use getset::Getters;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize, Serializer};
+use serde_repr::*;
+
+mod availability;
+mod category;
+mod conflicts;
+mod details;
+mod diff;
+mod merge;
+mod range;
+mod resource;
+mod workload;
+
+pub use availability::{find_free_slots, AvailabilityOptions, FreeSlot};
+pub use category::{EventCategory, EventKind};
+pub use conflicts::{find_conflicts, Conflict, ConflictKind, ConflictOptions, ConflictReport};
+pub use details::{DescriptionHints, EventDetails};
+pub use diff::{
+    diff_calendars, CalendarDiff, DiffOptions, EventChange, MatchedBy, ModifiedEvent, TimeSlot,
+};
+pub(crate) use merge::get_merged_calendar_with;
+pub use merge::{
+    get_merged_calendar, merge_calendars, CalendarSource, GetMergedCalendarQuery,
+    GetMergedCalendarResponse, MergedEvent,
+};
+pub use range::{
+    get_range, ChunkFailure, ChunkSize, GetRangeQuery, GetRangeResponse, RangeOptions,
+};
+pub use resource::{search_resources, Resource, ResourceType};
+pub use workload::{
+    compute_workload, CategoryHours, Hours, ModuleWorkload, SemesterWorkload, WeekWorkload,
+    Workload,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalendarView {
+    #[serde(rename(serialize = "agendaDay", deserialize = "day"))]
+    Day,
+    #[serde(rename = "week")]
+    Week,
+    #[serde(rename = "month")]
+    Month,
+}
+
+#[derive(Serialize_repr, Deserialize_repr, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum ColorBy {
+    EventCategory = 3,
+    Subject = 6,
+}
+
+#[derive(Clone)]
+pub struct GetCalendarQuery {
+    /// Federation id of the resource, see [`crate::auth::InfosResponse`] for
+    /// the logged in student's.
+    pub id: String,
+    pub resource_type: ResourceType,
+    pub token: SessionCookies,
+    pub start: CyuDate,
+    pub end: CyuDate,
+    pub view: CalendarView,
+    pub color_by: ColorBy,
+}
+
+#[derive(Serialize, Deserialize, Getters, Debug, Clone)]
+#[get = "pub"]
+#[serde(rename_all = "camelCase")]
+pub struct GetCalendarResponseElement {
+    id: String,
+    start: CyuDateTime,
+    end: Option<CyuDateTime>,
+    all_day: bool,
+    #[getset(skip)]
+    #[serde(serialize_with = "serialize_description")]
+    description: String,
+    background_color: String,
+    department: String,
+    faculty: Option<String>,
+    event_category: EventCategory,
+    sites: Option<Vec<String>>,
+    modules: Option<Vec<String>>,
+}
+fn parse_description(description: &str) -> String {
+    static LINEBREAKS_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\r\n|<br />)+").unwrap());
+    html_escape::decode_html_entities(LINEBREAKS_REGEX.replace_all(description, "\n").trim())
+        .to_string()
+}
+fn serialize_description<S: Serializer>(description: &str, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&parse_description(description))
+}
+impl GetCalendarResponseElement {
+    pub fn description(&self) -> String {
+        parse_description(&self.description)
+    }
+    pub fn details(&self) -> EventDetails {
+        EventDetails::parse(
+            &self.description(),
+            DescriptionHints {
+                category: &self.event_category,
+                sites: self.sites.as_deref().unwrap_or_default(),
+                modules: self.modules.as_deref().unwrap_or_default(),
+                department: &self.department,
+                faculty: self.faculty.as_deref(),
+            },
+        )
+    }
+}
+pub type GetCalendarResponse = Vec<GetCalendarResponseElement>;
+
+#[derive(Serialize)]
+struct GetCalendarRemotePayload {
+    #[serde(rename = "federationIds[]")]
+    id: String,
+    #[serde(rename = "resType")]
+    res_type: ResourceType,
+    start: CyuDate,
+    end: CyuDate,
+    #[serde(rename = "calView")]
+    view: CalendarView,
+    #[serde(rename = "colourScheme")]
+    color_by: ColorBy,
+}
+
+pub async fn get_calendar(
+    requester: &reqwest::Client,
+    base_url: &str,
+    query: GetCalendarQuery,
+) -> Result<GetCalendarResponse, Error> {
+    if query.start > query.end {
+        return Err(Error::DateRange {
+            start: query.start,
+            end: query.end,
+        });
+    }
+
+    let remote_payload = GetCalendarRemotePayload {
+        id: query.id,
+        res_type: query.resource_type,
+        start: query.start,
+        end: query.end,
+        view: query.view,
+        color_by: query.color_by,
+    };
+
+    let response = query
+        .token
+        .send(
+            requester
+                .post(format!("{base_url}/Home/GetCalendarData"))
+                .form(&remote_payload)
+                .header("Content-Type", "application/x-www-form-urlencoded"),
+        )
+        .await
+        .map_err(Error::Network)?;
+
+    if response.status().is_redirection() {
+        return Err(Error::Unauthorized);
+    }
+    if !response.status().is_success() {
+        return Err(Error::status(&response));
+    }
+
+    // An expired session gets an empty body rather than an error status
+    let body = response.text().await.map_err(Error::Network)?;
+    if body.trim().is_empty() {
+        return Err(Error::Unauthorized);
+    }
+    let calendar =
+        serde_json::from_str::<GetCalendarResponse>(&body).map_err(|source| Error::Json {
+            endpoint: "Home/GetCalendarData",
+            source,
+        })?;
+
+    Ok(calendar)
+}
+
+pub struct GetLimitsQuery<'a> {
+    pub id: &'a str,
+    pub resource_type: ResourceType,
+    pub token: &'a SessionCookies,
+    /// Date whose academic year is wanted, today when `None`.
+    pub date: Option<CyuDate>,
+}
+
+pub type GetLimitsResponse = (CyuDate, CyuDate);
+
+/// Reads the whole range the server lets us browse, which may span
+/// several academic years.
+async fn get_date_extents(
+    requester: &reqwest::Client,
+    base_url: &str,
+    timezone: chrono_tz::Tz,
+    query: &GetLimitsQuery<'_>,
+) -> Result<GetLimitsResponse, Error> {
+    let date = query
+        .date
+        .clone()
+        .unwrap_or_else(|| CyuDate::today_in(timezone));
+    let page_response = query
+        .token
+        .send(requester.get(format!("{}/?CalendarViewType=Month&CalendarDate={} 00:00:00&EntityType={}&FederationIds={}&CalendarViewStr=month&EntityTypeAsIntegerString={}&IsValid=True&NotAllowedToBrowse=False", base_url, date.format("%m/%d/%Y"), query.resource_type.entity_type(), query.id, query.resource_type.code())))
+        .await
+        .map_err(Error::Network)?;
+
+    if page_response.status().is_redirection()
+        && page_response
+            .headers()
+            .get("location")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .ends_with("/Login")
+    {
+        return Err(Error::Unauthorized);
+    }
+    if !page_response.status().is_success() {
+        return Err(Error::status(&page_response));
+    }
+
+    let page_text = page_response.text().await.map_err(Error::Network)?;
+    let (date1, date2) = match scrape::date_extents(&page_text) {
+        Ok(extents) => extents,
+        Err(_) if scrape::is_login_page(&page_text) => return Err(Error::Unauthorized),
+        Err(error) => return Err(error),
+    };
+    if date1 > date2 {
+        return Err(Error::DateRange {
+            start: date1,
+            end: date2,
+        });
+    }
+    Ok((date1, date2))
+}
+
+/// Lists the academic years the server exposes, oldest first. `timezone` is
+/// that of the instance, in which today is taken when the query has no date.
+pub async fn get_academic_years(
+    requester: &reqwest::Client,
+    base_url: &str,
+    timezone: chrono_tz::Tz,
+    query: GetLimitsQuery<'_>,
+) -> Result<Vec<AcademicYear>, Error> {
+    let (start, end) = get_date_extents(requester, base_url, timezone, &query).await?;
+    Ok(AcademicYear::split(&start, &end))
+}
+
+/// Limits of the academic year containing `date`. When the server does not
+/// expose that year, the next exposed one is used, or the last one if `date`
+/// is past them all.
+pub(crate) fn pick_academic_year(
+    years: &[AcademicYear],
+    date: &CyuDate,
+) -> Result<GetLimitsResponse, Error> {
+    let year = years
+        .iter()
+        .find(|year| year.contains(date) || year.start > *date)
+        .or(years.last())
+        .cloned()
+        .ok_or(Error::NoAcademicYear)?;
+    Ok((year.start, year.end))
+}
+
+/// Limits of the academic year containing the reference date, see
+/// [`pick_academic_year`].
+pub async fn get_limits(
+    requester: &reqwest::Client,
+    base_url: &str,
+    timezone: chrono_tz::Tz,
+    query: GetLimitsQuery<'_>,
+) -> Result<GetLimitsResponse, Error> {
+    get_limits_with(
+        &Direct {
+            requester,
+            base_url,
+            timezone,
+        },
+        query,
+    )
+    .await
+}
+
+pub(crate) async fn get_limits_with(
+    backend: &dyn Backend,
+    query: GetLimitsQuery<'_>,
+) -> Result<GetLimitsResponse, Error> {
+    let date = query.date.clone().unwrap_or_else(|| backend.today());
+    let years = backend
+        .get_academic_years(GetLimitsQuery {
+            date: Some(date.clone()),
+            ..query
+        })
+        .await?;
+    pick_academic_year(&years, &date)
+}
+
+pub struct GetAllQuery {
+    pub id: String,
+    pub token: SessionCookies,
+    pub color_by: ColorBy,
+}
+
+/// Fetches the whole current academic year, see [`get_limits`] and [`get_range`].
+pub async fn get_all(
+    requester: &reqwest::Client,
+    base_url: &str,
+    timezone: chrono_tz::Tz,
+    query: GetAllQuery,
+) -> Result<GetRangeResponse, Error> {
+    get_all_with(
+        &Direct {
+            requester,
+            base_url,
+            timezone,
+        },
+        query,
+    )
+    .await
+}
+
+pub(crate) async fn get_all_with(
+    backend: &dyn Backend,
+    query: GetAllQuery,
+) -> Result<GetRangeResponse, Error> {
+    let (start, end) = get_limits_with(
+        backend,
+        GetLimitsQuery {
+            id: &query.id,
+            resource_type: ResourceType::Student,
+            token: &query.token,
+            date: None,
+        },
+    )
+    .await?;
+
+    backend
+        .get_range(GetRangeQuery {
+            id: query.id,
+            resource_type: ResourceType::Student,
+            token: query.token,
+            start,
+            end,
+            color_by: query.color_by,
+            options: RangeOptions::default(),
+        })
+        .await
+}
@@ -0,0 +1,196 @@
+use super::{
+    get_calendar, CalendarView, ColorBy, GetCalendarQuery, GetCalendarResponse, ResourceType,
+};
+use crate::cookies::SessionCookies;
+use crate::errors::Error;
+use crate::utils::CyuDate;
+use chrono::{Datelike as _, Days, Months};
+use futures::stream::{self, StreamExt as _};
+use std::collections::HashSet;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkSize {
+    Week,
+    Month,
+}
+
+impl ChunkSize {
+    fn view(self) -> CalendarView {
+        match self {
+            Self::Week => CalendarView::Week,
+            Self::Month => CalendarView::Month,
+        }
+    }
+
+    /// Last day of the chunk starting at `start`.
+    fn end_of_chunk(self, start: &CyuDate) -> CyuDate {
+        let end = match self {
+            Self::Week => {
+                let days_left = 6 - start.weekday().num_days_from_monday();
+                start.checked_add_days(Days::new(days_left.into()))
+            }
+            Self::Month => start
+                .with_day(1)
+                .and_then(|first| first.checked_add_months(Months::new(1)))
+                .and_then(|next| next.pred_opt()),
+        };
+        end.map_or_else(|| start.clone(), CyuDate::from)
+    }
+
+    /// Splits `start..=end` into chunks aligned on weeks or months.
+    pub fn split(self, start: &CyuDate, end: &CyuDate) -> Vec<(CyuDate, CyuDate)> {
+        let mut chunks = Vec::new();
+        let mut chunk_start = start.clone();
+        while chunk_start <= *end {
+            let chunk_end = self.end_of_chunk(&chunk_start).min(end.clone());
+            let Some(next) = chunk_end.succ_opt() else {
+                chunks.push((chunk_start, chunk_end));
+                break;
+            };
+            chunks.push((chunk_start, chunk_end));
+            chunk_start = next.into();
+        }
+        chunks
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RangeOptions {
+    pub chunk_size: ChunkSize,
+    /// Maximum number of chunks requested at the same time.
+    pub concurrency: usize,
+    /// How many times a chunk that failed transiently is requested again,
+    /// see [`Error::is_transient`].
+    pub retries: usize,
+    /// Wait before the first retry, doubled before each following one.
+    pub retry_delay: Duration,
+}
+
+impl Default for RangeOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: ChunkSize::Month,
+            concurrency: 4,
+            retries: 2,
+            retry_delay: Duration::from_millis(500),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct GetRangeQuery {
+    pub id: String,
+    pub resource_type: ResourceType,
+    pub token: SessionCookies,
+    pub start: CyuDate,
+    pub end: CyuDate,
+    pub color_by: ColorBy,
+    pub options: RangeOptions,
+}
+
+/// A chunk that kept failing after every retry.
+#[derive(Debug)]
+pub struct ChunkFailure {
+    pub start: CyuDate,
+    pub end: CyuDate,
+    pub error: Error,
+}
+
+#[derive(Debug, Default)]
+pub struct GetRangeResponse {
+    /// Events of every chunk that succeeded, sorted by start and
+    /// deduplicated by id.
+    pub events: GetCalendarResponse,
+    pub failures: Vec<ChunkFailure>,
+}
+
+impl GetRangeResponse {
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+async fn get_chunk(
+    requester: &reqwest::Client,
+    base_url: &str,
+    query: &GetRangeQuery,
+    start: CyuDate,
+    end: CyuDate,
+) -> Result<GetCalendarResponse, ChunkFailure> {
+    let mut attempt = 0;
+    loop {
+        let result = get_calendar(
+            requester,
+            base_url,
+            GetCalendarQuery {
+                id: query.id.clone(),
+                resource_type: query.resource_type,
+                token: query.token.clone(),
+                start: start.clone(),
+                end: end.clone(),
+                view: query.options.chunk_size.view(),
+                color_by: query.color_by,
+            },
+        )
+        .await;
+        match result {
+            Ok(events) => return Ok(events),
+            Err(error) if error.is_transient() && attempt < query.options.retries => {
+                let backoff = 1u32 << attempt.min(16);
+                tokio::time::sleep(query.options.retry_delay.saturating_mul(backoff)).await;
+                attempt += 1;
+            }
+            Err(error) => return Err(ChunkFailure { start, end, error }),
+        }
+    }
+}
+
+/// Fetches `start..=end` chunk by chunk.
+///
+/// Failed chunks are reported in [`GetRangeResponse::failures`] rather than
+/// failing the whole range, unless every chunk failed or the session expired.
+pub async fn get_range(
+    requester: &reqwest::Client,
+    base_url: &str,
+    query: GetRangeQuery,
+) -> Result<GetRangeResponse, Error> {
+    if query.start > query.end {
+        return Err(Error::DateRange {
+            start: query.start,
+            end: query.end,
+        });
+    }
+
+    let chunks = query.options.chunk_size.split(&query.start, &query.end);
+    let nb_chunks = chunks.len();
+    let results = stream::iter(chunks)
+        .map(|(start, end)| get_chunk(requester, base_url, &query, start, end))
+        .buffer_unordered(query.options.concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut response = GetRangeResponse::default();
+    let mut seen = HashSet::new();
+    for result in results {
+        match result {
+            Ok(events) => response.events.extend(
+                events
+                    .into_iter()
+                    .filter(|event| seen.insert(event.id().clone())),
+            ),
+            Err(ChunkFailure {
+                error: Error::Unauthorized,
+                ..
+            }) => return Err(Error::Unauthorized),
+            Err(failure) => response.failures.push(failure),
+        }
+    }
+
+    if response.failures.len() == nb_chunks {
+        return Err(response.failures.pop().unwrap().error);
+    }
+    response.events.sort_by(|a, b| a.start().cmp(b.start()));
+    response.failures.sort_by(|a, b| a.start.cmp(&b.start));
+    Ok(response)
+}
@@ -0,0 +1,172 @@
+use crate::cookies::SessionCookies;
+use crate::errors::Error;
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use std::collections::HashSet;
+use std::fmt::Display;
+
+/// Kind of resource a timetable belongs to, sent to Celcat as `resType`.
+#[derive(Serialize_repr, Deserialize_repr, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(u16)]
+pub enum ResourceType {
+    Module = 100,
+    /// Teachers and other staff members
+    Staff = 101,
+    Room = 102,
+    Group = 103,
+    #[default]
+    Student = 104,
+}
+
+impl ResourceType {
+    pub const ALL: [Self; 5] = [
+        Self::Module,
+        Self::Staff,
+        Self::Room,
+        Self::Group,
+        Self::Student,
+    ];
+
+    /// The `resType` code Celcat uses.
+    pub fn code(self) -> u16 {
+        self as u16
+    }
+
+    /// The `EntityType` name Celcat uses in page URLs.
+    pub fn entity_type(self) -> &'static str {
+        match self {
+            Self::Module => "Module",
+            Self::Staff => "Staff",
+            Self::Room => "Room",
+            Self::Group => "Group",
+            Self::Student => "Student",
+        }
+    }
+
+    pub fn from_code(code: u16) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.code() == code)
+    }
+
+    /// Human readable name, in French like the rest of CYU.
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Self::Module => "Module",
+            Self::Staff => "Enseignant",
+            Self::Room => "Salle",
+            Self::Group => "Groupe",
+            Self::Student => "Étudiant",
+        }
+    }
+}
+
+impl Display for ResourceType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+/// A timetable found by [`search_resources`].
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Resource {
+    /// Federation id, to give to [`super::GetCalendarQuery`].
+    pub id: String,
+    pub display_name: String,
+    pub resource_type: ResourceType,
+    pub department: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RemoteResource {
+    id: String,
+    text: String,
+    dept: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SearchResourcesRemoteResponse {
+    /// Number of matches over every page.
+    #[serde(default)]
+    total: Option<usize>,
+    results: Vec<RemoteResource>,
+}
+
+/// Number of results asked for at once, Celcat pages the rest.
+const SEARCH_PAGE_SIZE: usize = 50;
+/// Pages read at most, in case the server keeps answering full pages.
+const SEARCH_MAX_PAGES: usize = 20;
+
+async fn search_resources_page(
+    requester: &reqwest::Client,
+    base_url: &str,
+    token: &SessionCookies,
+    kind: ResourceType,
+    query: &str,
+    page: usize,
+) -> Result<SearchResourcesRemoteResponse, Error> {
+    let response = token
+        .send(
+            requester
+                .get(format!("{base_url}/Home/ReadResourceListItems"))
+                .query(&[
+                    ("myResources", "false"),
+                    ("searchTerm", query),
+                    ("pageSize", &SEARCH_PAGE_SIZE.to_string()),
+                    ("pageNumber", &page.to_string()),
+                    ("resType", &kind.to_string()),
+                ]),
+        )
+        .await
+        .map_err(Error::Network)?;
+
+    if response.status().is_redirection() {
+        return Err(Error::Unauthorized);
+    }
+    if !response.status().is_success() {
+        return Err(Error::status(&response));
+    }
+
+    let body = response.text().await.map_err(Error::Network)?;
+    serde_json::from_str(&body).map_err(|source| Error::Json {
+        endpoint: "Home/ReadResourceListItems",
+        source,
+    })
+}
+
+/// Looks up resources of a kind whose name contains `query`, the way the
+/// resource picker of the Celcat page does, going through every page.
+pub async fn search_resources(
+    requester: &reqwest::Client,
+    base_url: &str,
+    token: &SessionCookies,
+    kind: ResourceType,
+    query: &str,
+) -> Result<Vec<Resource>, Error> {
+    let mut resources = Vec::new();
+    let mut ids = HashSet::new();
+    for page in 1..=SEARCH_MAX_PAGES {
+        let response = search_resources_page(requester, base_url, token, kind, query, page).await?;
+        let is_last_page = response.results.len() < SEARCH_PAGE_SIZE;
+        let count = resources.len();
+        resources.extend(
+            response
+                .results
+                .into_iter()
+                .filter(|result| ids.insert(result.id.clone()))
+                .map(|result| Resource {
+                    id: result.id,
+                    display_name: html_escape::decode_html_entities(&result.text).into_owned(),
+                    resource_type: kind,
+                    department: result.dept.filter(|dept| !dept.is_empty()),
+                }),
+        );
+        // A server ignoring `pageNumber` answers the same page again
+        let is_repeated = resources.len() == count;
+        if is_last_page
+            || is_repeated
+            || response.total.is_some_and(|total| resources.len() >= total)
+        {
+            break;
+        }
+    }
+    Ok(resources)
+}
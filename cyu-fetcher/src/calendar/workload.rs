@@ -0,0 +1,223 @@
+use super::{EventKind, GetCalendarResponseElement};
+use crate::utils::{CyuDate, CyuDateTime, Semester};
+use chrono::{Datelike as _, Days, TimeDelta};
+use serde::ser::SerializeStruct as _;
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+
+/// Time spent in class, split at the reference time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Hours {
+    pub done: TimeDelta,
+    pub left: TimeDelta,
+}
+
+impl Hours {
+    pub fn total(&self) -> TimeDelta {
+        self.done + self.left
+    }
+
+    fn add(&mut self, other: Hours) {
+        self.done += other.done;
+        self.left += other.left;
+    }
+}
+
+/// Serialized as decimal hours, with the total.
+impl Serialize for Hours {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let hours = |duration: TimeDelta| duration.num_minutes() as f64 / 60.0;
+        let mut state = serializer.serialize_struct("Hours", 3)?;
+        state.serialize_field("done", &hours(self.done))?;
+        state.serialize_field("left", &hours(self.left))?;
+        state.serialize_field("total", &hours(self.total()))?;
+        state.end()
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CategoryHours {
+    pub category: EventKind,
+    pub hours: Hours,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ModuleWorkload {
+    /// `None` for the events whose module is unknown.
+    pub module: Option<String>,
+    pub hours: Hours,
+    pub by_category: Vec<CategoryHours>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct WeekWorkload {
+    pub monday: CyuDate,
+    pub hours: Hours,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SemesterWorkload {
+    pub semester: Semester,
+    pub hours: Hours,
+    pub by_category: Vec<CategoryHours>,
+}
+
+/// Hours of class of a calendar, done and left.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct Workload {
+    pub total: Hours,
+    pub by_category: Vec<CategoryHours>,
+    /// Sorted by module name, unknown modules last.
+    pub by_module: Vec<ModuleWorkload>,
+    pub by_week: Vec<WeekWorkload>,
+    pub by_semester: Vec<SemesterWorkload>,
+}
+
+impl Workload {
+    /// Module called `name`, ignoring case.
+    pub fn module(&self, name: &str) -> Option<&ModuleWorkload> {
+        self.by_module.iter().find(|workload| {
+            workload
+                .module
+                .as_deref()
+                .is_some_and(|module| module.eq_ignore_ascii_case(name))
+        })
+    }
+
+    pub fn category(&self, category: &EventKind) -> Hours {
+        find_category(&self.by_category, category)
+    }
+}
+
+impl ModuleWorkload {
+    /// Hours of `category` in this module, e.g. the tutorials left.
+    pub fn category(&self, category: &EventKind) -> Hours {
+        find_category(&self.by_category, category)
+    }
+}
+
+fn find_category(by_category: &[CategoryHours], category: &EventKind) -> Hours {
+    by_category
+        .iter()
+        .find(|hours| hours.category == *category)
+        .map(|hours| hours.hours)
+        .unwrap_or_default()
+}
+
+/// Hours per category, sorted by category code.
+#[derive(Default)]
+struct Categories(BTreeMap<String, CategoryHours>);
+
+impl Categories {
+    fn add(&mut self, category: &EventKind, hours: Hours) {
+        self.0
+            .entry(category.as_str().to_owned())
+            .or_insert_with(|| CategoryHours {
+                category: category.clone(),
+                hours: Hours::default(),
+            })
+            .hours
+            .add(hours);
+    }
+
+    fn total(&self) -> Hours {
+        let mut total = Hours::default();
+        for category in self.0.values() {
+            total.add(category.hours);
+        }
+        total
+    }
+
+    fn into_vec(self) -> Vec<CategoryHours> {
+        self.0.into_values().collect()
+    }
+}
+
+/// Hours of `event` before and after `now`, `None` for events that are not
+/// classes: all-day events, holidays and events without an end.
+fn event_hours(event: &GetCalendarResponseElement, now: &CyuDateTime) -> Option<Hours> {
+    if *event.all_day() || event.event_category().kind == EventKind::Holiday {
+        return None;
+    }
+    let (start, end) = (**event.start(), **event.end().as_ref()?);
+    if end <= start {
+        return None;
+    }
+    let split = (**now).clamp(start, end);
+    Some(Hours {
+        done: split - start,
+        left: end - split,
+    })
+}
+
+fn module(event: &GetCalendarResponseElement) -> Option<String> {
+    event
+        .details()
+        .module
+        .or_else(|| event.modules().as_ref()?.first().cloned())
+        .filter(|module| !module.is_empty())
+}
+
+/// Aggregates `events` into hours per category, module, week and semester,
+/// counting what took place before `now` as done.
+pub fn compute_workload(events: &[GetCalendarResponseElement], now: &CyuDateTime) -> Workload {
+    let mut by_category = Categories::default();
+    // `None` sorts first in a BTreeMap, it is moved last afterwards
+    let mut by_module: BTreeMap<Option<String>, Categories> = BTreeMap::new();
+    let mut by_week: BTreeMap<CyuDate, Hours> = BTreeMap::new();
+    let mut by_semester: BTreeMap<Semester, Categories> = BTreeMap::new();
+
+    for event in events {
+        let Some(hours) = event_hours(event, now) else {
+            continue;
+        };
+        let category = &event.event_category().kind;
+        let date: CyuDate = event.start().date().into();
+        let days_from_monday = date.weekday().num_days_from_monday();
+        let monday: CyuDate = (*date - Days::new(days_from_monday.into())).into();
+
+        by_category.add(category, hours);
+        by_module
+            .entry(module(event))
+            .or_default()
+            .add(category, hours);
+        by_week.entry(monday).or_default().add(hours);
+        by_semester
+            .entry(Semester::containing(&date))
+            .or_default()
+            .add(category, hours);
+    }
+
+    let mut by_module = by_module
+        .into_iter()
+        .map(|(module, categories)| ModuleWorkload {
+            module,
+            hours: categories.total(),
+            by_category: categories.into_vec(),
+        })
+        .collect::<Vec<_>>();
+    if by_module
+        .first()
+        .is_some_and(|first| first.module.is_none())
+    {
+        by_module.rotate_left(1);
+    }
+
+    Workload {
+        total: by_category.total(),
+        by_category: by_category.into_vec(),
+        by_module,
+        by_week: by_week
+            .into_iter()
+            .map(|(monday, hours)| WeekWorkload { monday, hours })
+            .collect(),
+        by_semester: by_semester
+            .into_iter()
+            .map(|(semester, categories)| SemesterWorkload {
+                semester,
+                hours: categories.total(),
+                by_category: categories.into_vec(),
+            })
+            .collect(),
+    }
+}
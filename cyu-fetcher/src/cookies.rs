@@ -0,0 +1,142 @@
+use reqwest::cookie::{CookieStore, Jar};
+use reqwest::header::{COOKIE, SET_COOKIE};
+use reqwest::Url;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+/// Cookies of a CYU session, what proves who we are to Celcat.
+///
+/// Only names and values are kept, as a cookie store sends them to the
+/// calendar: attributes such as `Path` or `HttpOnly` and the cookies of
+/// other sites are left out. Serializable, so that frontends can save the
+/// session and resume it later.
+///
+/// Clones share the same cookies, which the `Set-Cookie` headers of every
+/// response update, so that a session renewed by Celcat keeps working
+/// whichever clone sends the next request.
+#[derive(Debug, Clone, Default)]
+pub struct SessionCookies(Arc<RwLock<BTreeMap<String, String>>>);
+
+impl SessionCookies {
+    /// Cookies `store` would send to `url`.
+    pub fn from_store(store: &dyn CookieStore, url: &Url) -> Self {
+        Self::new(Self::read_store(store, url))
+    }
+
+    fn new(cookies: BTreeMap<String, String>) -> Self {
+        Self(Arc::new(RwLock::new(cookies)))
+    }
+
+    fn read_store(store: &dyn CookieStore, url: &Url) -> BTreeMap<String, String> {
+        store
+            .cookies(url)
+            .and_then(|header| header.to_str().ok().map(Self::parse_header))
+            .unwrap_or_default()
+    }
+
+    /// Parses a `Cookie` header, `name=value; name=value`.
+    fn parse_header(header: &str) -> BTreeMap<String, String> {
+        header
+            .split(';')
+            .filter_map(|cookie| cookie.trim().split_once('='))
+            .filter(|(name, _)| !name.is_empty())
+            .map(|(name, value)| (name.to_owned(), value.to_owned()))
+            .collect()
+    }
+
+    /// The cookies as they are now.
+    pub(crate) fn snapshot(&self) -> BTreeMap<String, String> {
+        self.0.read().unwrap().clone()
+    }
+
+    /// Whether `other` is a clone of these cookies, rather than the same
+    /// values by chance.
+    pub(crate) fn same_session(&self, other: &SessionCookies) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.read().unwrap().is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.0.read().unwrap().get(name).cloned()
+    }
+
+    /// Value of the `Cookie` header sending these cookies.
+    pub fn header(&self) -> String {
+        self.0
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    /// Sends `request` with these cookies, and keeps those the response
+    /// sets.
+    pub(crate) async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        let response = request.header(COOKIE, self.header()).send().await?;
+        self.store_response(&response);
+        Ok(response)
+    }
+
+    /// Updates the cookies with the `Set-Cookie` headers of `response`, the
+    /// way a cookie store would, expired ones being removed.
+    fn store_response(&self, response: &reqwest::Response) {
+        if !response.headers().contains_key(SET_COOKIE) {
+            return;
+        }
+        let url = response.url();
+        let mut cookies = self.0.write().unwrap();
+        let store = Jar::default();
+        for (name, value) in cookies.iter() {
+            store.add_cookie_str(&format!("{name}={value}; Path=/"), url);
+        }
+        store_response_cookies(&store, response);
+        *cookies = Self::read_store(&store, url);
+    }
+}
+
+impl PartialEq for SessionCookies {
+    fn eq(&self, other: &Self) -> bool {
+        self.same_session(other) || self.snapshot() == other.snapshot()
+    }
+}
+
+impl Eq for SessionCookies {}
+
+impl Serialize for SessionCookies {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.snapshot().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SessionCookies {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        BTreeMap::deserialize(deserializer).map(Self::new)
+    }
+}
+
+impl<N: Into<String>, V: Into<String>> FromIterator<(N, V)> for SessionCookies {
+    fn from_iter<T: IntoIterator<Item = (N, V)>>(iter: T) -> Self {
+        Self::new(
+            iter.into_iter()
+                .map(|(name, value)| (name.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+/// Saves the `Set-Cookie` headers of `response` in `store`.
+pub(crate) fn store_response_cookies(store: &dyn CookieStore, response: &reqwest::Response) {
+    store.set_cookies(
+        &mut response.headers().get_all(SET_COOKIE).iter(),
+        response.url(),
+    );
+}
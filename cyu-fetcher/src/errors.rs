@@ -1,15 +1,100 @@
+use crate::utils::CyuDate;
 use std::fmt::Display;
 
 #[derive(Debug)]
 pub enum Error {
-    Remote,
+    /// The HTTP client could not be built.
+    Client(reqwest::Error),
+    /// The root of the calendar is not an HTTP(S) URL.
+    BaseUrl { url: String, reason: String },
+    /// The request could not be sent or its body could not be read.
+    Network(reqwest::Error),
+    /// CYU answered with a status we do not know how to handle.
+    Status {
+        url: String,
+        status: reqwest::StatusCode,
+    },
+    /// A scraped page no longer contains what we look for, which usually
+    /// means CYU changed its layout.
+    Layout {
+        page: &'static str,
+        marker: &'static str,
+        /// Where the marker was expected, when the page could be read.
+        fragment: Option<String>,
+    },
+    /// A JSON endpoint answered with something we cannot decode.
+    Json {
+        endpoint: &'static str,
+        source: serde_json::Error,
+    },
+    /// The requested or advertised date range is empty.
+    DateRange { start: CyuDate, end: CyuDate },
+    /// The calendar exposes no academic year to browse.
+    NoAcademicYear,
+    /// The credentials were refused or the session expired.
     Unauthorized,
 }
 
+impl Error {
+    pub(crate) fn status(response: &reqwest::Response) -> Self {
+        Self::Status {
+            url: response.url().to_string(),
+            status: response.status(),
+        }
+    }
+
+    /// Whether this error points at a scraper that needs updating rather
+    /// than at a transient failure.
+    pub fn is_layout_change(&self) -> bool {
+        matches!(self, Self::Layout { .. } | Self::Json { .. })
+    }
+
+    /// Where the marker of a layout error was expected, for logs: it is
+    /// part of a CYU page and is left out of the message.
+    pub fn fragment(&self) -> Option<&str> {
+        match self {
+            Self::Layout { fragment, .. } => fragment.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Whether the same request may succeed later: the network failed or
+    /// CYU had a server error.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Network(_) => true,
+            Self::Status { status, .. } => status.is_server_error(),
+            _ => false,
+        }
+    }
+}
+
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        match self {
+            Self::Client(err) => write!(f, "failed to build HTTP client: {err}"),
+            Self::BaseUrl { url, reason } => write!(f, "invalid base URL {url:?}: {reason}"),
+            Self::Network(err) => write!(f, "failed to reach CYU: {err}"),
+            Self::Status { url, status } => write!(f, "unexpected status {status} from {url}"),
+            Self::Layout { page, marker, .. } => {
+                write!(f, "page layout changed: {marker} not found on {page}")
+            }
+            Self::Json { endpoint, source } => {
+                write!(f, "failed to decode {endpoint} response: {source}")
+            }
+            Self::DateRange { start, end } => write!(f, "invalid date range: {start} to {end}"),
+            Self::NoAcademicYear => write!(f, "no academic year to browse"),
+            Self::Unauthorized => write!(f, "unauthorized"),
+        }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Client(err) | Self::Network(err) => Some(err),
+            Self::Json { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,278 @@
+mod recurrence;
+mod timezone;
+
+pub use recurrence::{collapse_weekly, Recurrence, Series};
+
+use crate::calendar::{EventKind, GetCalendarResponseElement};
+use crate::sites::SiteRegistry;
+use crate::utils::{CyuDateTime, TIMEZONE};
+use chrono::{DateTime, Datelike as _, Days, TimeDelta, Utc};
+use icalendar::{
+    Calendar, CalendarDateTime, Component as _, DatePerhapsTime, Event, EventLike as _,
+};
+
+/// What to do with timed events Celcat gives no end to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissingEnd {
+    Skip,
+    /// Export them as lasting this long.
+    Duration(TimeDelta),
+}
+
+#[derive(Debug, Clone)]
+pub struct IcsOptions<'a> {
+    pub calendar_name: String,
+    /// Summary of the events. `{category}`, `{category_name}`, `{module}`,
+    /// `{rooms}`, `{teachers}` and `{groups}` are replaced by the details of
+    /// the event. The spaces, empty brackets and trailing separators left by
+    /// empty ones are removed.
+    ///
+    /// `None` gives the category, followed by the module for lectures and
+    /// tutorials.
+    pub title_template: Option<String>,
+    pub include_description: bool,
+    pub missing_end: MissingEnd,
+    /// Used to add the address and coordinates of events, `None` to only
+    /// give their rooms.
+    pub sites: Option<&'a SiteRegistry>,
+    /// Timezone the times of the events are in, that of the Celcat
+    /// instance.
+    pub timezone: chrono_tz::Tz,
+    /// Export events repeating every week as one event with an `RRULE`, see
+    /// [`collapse_weekly`].
+    pub collapse_recurrences: bool,
+}
+
+impl Default for IcsOptions<'_> {
+    fn default() -> Self {
+        Self {
+            calendar_name: String::from("CYU Calendar"),
+            title_template: None,
+            include_description: true,
+            missing_end: MissingEnd::Duration(TimeDelta::hours(1)),
+            sites: Some(SiteRegistry::embedded()),
+            timezone: TIMEZONE,
+            collapse_recurrences: false,
+        }
+    }
+}
+
+fn title(event: &GetCalendarResponseElement, template: Option<&str>) -> String {
+    let details = event.details();
+    let category = event.event_category();
+    let template = match (template, &category.kind) {
+        (Some(template), _) => template,
+        (None, EventKind::Lecture | EventKind::Tutorial) => "{category} {module}",
+        (None, _) => "{category}",
+    };
+    let title = template
+        .replace("{category_name}", category.display_name())
+        .replace("{category}", category.as_str())
+        .replace("{module}", details.module.as_deref().unwrap_or_default())
+        .replace("{rooms}", &details.rooms.join(", "))
+        .replace("{teachers}", &details.teachers.join(", "))
+        .replace("{groups}", &details.groups.join(", "));
+    let title = title
+        .replace("()", "")
+        .replace("[]", "")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    title
+        .trim_matches(|c: char| c.is_whitespace() || "-–,:|/".contains(c))
+        .to_owned()
+}
+
+/// `DTSTART` and `DTEND` of `event`, `None` when it is skipped.
+fn times(
+    event: &GetCalendarResponseElement,
+    options: &IcsOptions,
+) -> Option<(DatePerhapsTime, DatePerhapsTime)> {
+    let start = event.start();
+    match (event.all_day(), event.end()) {
+        (true, end) => {
+            let first = start.date();
+            // DTEND of all-day events is the day after the last one
+            let after = end
+                .as_ref()
+                .map(|end| end.date())
+                .filter(|end| *end > first)
+                .or_else(|| first.checked_add_days(Days::new(1)))
+                .unwrap_or(first);
+            Some((first.into(), after.into()))
+        }
+        (false, end) => {
+            let start = start.as_utc_in(options.timezone);
+            let end = match (end, options.missing_end) {
+                (Some(end), _) => end.as_utc_in(options.timezone),
+                (None, MissingEnd::Duration(duration)) => start + duration,
+                (None, MissingEnd::Skip) => return None,
+            };
+            Some((start.into(), end.into()))
+        }
+    }
+}
+
+/// `datetime` in the local time of `timezone`, with its `TZID`.
+fn local(datetime: &CyuDateTime, timezone: chrono_tz::Tz) -> CalendarDateTime {
+    CalendarDateTime::WithTimezone {
+        date_time: **datetime,
+        tzid: timezone.name().to_owned(),
+    }
+}
+
+/// The VEVENT of `event`, but for its times.
+fn new_event(
+    event: &GetCalendarResponseElement,
+    options: &IcsOptions,
+    timestamp: DateTime<Utc>,
+) -> Event {
+    let mut ievent = Event::new();
+    ievent
+        .uid(&format!("{}@cyu-calendar", event.id()))
+        .timestamp(timestamp)
+        .summary(&title(event, options.title_template.as_deref()));
+    if options.include_description {
+        ievent.description(&event.description());
+    }
+
+    let details = event.details();
+    let location = options
+        .sites
+        .and_then(|sites| sites.locate(event).ok().flatten());
+    let place = location.map(|location| {
+        location
+            .address()
+            .map_or_else(|| location.name(), ToOwned::to_owned)
+    });
+    let location_text = details
+        .rooms
+        .iter()
+        .cloned()
+        .chain(place)
+        .collect::<Vec<_>>()
+        .join(", ");
+    if !location_text.is_empty() {
+        ievent.location(&location_text);
+    }
+    if let Some(location) = location {
+        let [latitude, longitude] = location.coordinates();
+        ievent.add_property("GEO", format!("{latitude};{longitude}"));
+    }
+
+    let category = event.event_category().as_str();
+    if !category.is_empty() {
+        ievent.add_property("CATEGORIES", category);
+    }
+    ievent.done()
+}
+
+fn single_event(
+    event: &GetCalendarResponseElement,
+    options: &IcsOptions,
+    timestamp: DateTime<Utc>,
+) -> Option<Event> {
+    let (start, end) = times(event, options)?;
+    let mut ievent = new_event(event, options, timestamp);
+    Some(ievent.starts(start).ends(end).done())
+}
+
+fn series_event(series: &Series, options: &IcsOptions, timestamp: DateTime<Utc>) -> Event {
+    let timezone = options.timezone;
+    let end: CyuDateTime = (**series.start() + series.duration()).into();
+    let mut event = new_event(series.event, options, timestamp);
+    event
+        .starts(local(series.start(), timezone))
+        .ends(local(&end, timezone))
+        // UNTIL is in UTC when DTSTART has a timezone
+        .add_property(
+            "RRULE",
+            format!(
+                "FREQ=WEEKLY;UNTIL={}",
+                series.until.as_utc_in(timezone).format("%Y%m%dT%H%M%SZ")
+            ),
+        );
+    for exdate in &series.exdates {
+        event.exdate(local(exdate, timezone));
+    }
+    for rdate in &series.rdates {
+        event.rdate(local(rdate, timezone));
+    }
+    event.done()
+}
+
+/// Renders `events` as an iCalendar file, RFC 5545, stamped with
+/// `timestamp`, the time of the export.
+pub fn to_icalendar<'a>(
+    events: impl IntoIterator<Item = &'a GetCalendarResponseElement>,
+    options: &IcsOptions,
+    timestamp: DateTime<Utc>,
+) -> String {
+    let mut calendar = Calendar::empty();
+    calendar
+        .append_property(("VERSION", "2.0"))
+        .append_property(("PRODID", "-//cyu-calendar//cyu-fetcher//FR"))
+        .append_property(("CALSCALE", "GREGORIAN"))
+        .name(&options.calendar_name);
+
+    let mut vtimezone = Vec::new();
+    let mut ievents = Vec::new();
+    if options.collapse_recurrences {
+        let recurrences = collapse_weekly(events);
+        // Series are given in the local time, with the rules of the
+        // timezone for every year from the first to the last of their dates
+        let years = recurrences
+            .iter()
+            .filter_map(|recurrence| match recurrence {
+                Recurrence::Weekly(series) => Some(series),
+                Recurrence::Single(_) => None,
+            })
+            .flat_map(|series| {
+                [series.start(), &series.until]
+                    .into_iter()
+                    .chain(&series.rdates)
+            })
+            .map(|datetime| datetime.year())
+            .fold(None, |years, year| match years {
+                None => Some((year, year)),
+                Some((first, last)) => Some((year.min(first), year.max(last))),
+            });
+        if let Some((first, last)) = years {
+            vtimezone = timezone::vtimezone(options.timezone, first..=last);
+        }
+        for recurrence in &recurrences {
+            match recurrence {
+                Recurrence::Single(event) => {
+                    ievents.extend(single_event(event, options, timestamp))
+                }
+                Recurrence::Weekly(series) => {
+                    ievents.push(series_event(series, options, timestamp))
+                }
+            }
+        }
+    } else {
+        ievents.extend(
+            events
+                .into_iter()
+                .filter_map(|event| single_event(event, options, timestamp)),
+        );
+    }
+
+    // icalendar gives every component a DTSTAMP and a UID, which a
+    // VTIMEZONE cannot have, so it is written between the properties of the
+    // calendar and its events
+    let header = calendar.to_string();
+    let mut ics = header
+        .strip_suffix("END:VCALENDAR\r\n")
+        .unwrap_or(&header)
+        .to_owned();
+    for line in vtimezone {
+        ics.push_str(&line);
+        ics.push_str("\r\n");
+    }
+    for ievent in &ievents {
+        ics.push_str(&ievent.to_string());
+    }
+    ics.push_str("END:VCALENDAR\r\n");
+    ics
+}
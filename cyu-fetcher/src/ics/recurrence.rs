@@ -0,0 +1,222 @@
+use crate::calendar::GetCalendarResponseElement;
+use crate::utils::CyuDateTime;
+use chrono::{Datelike as _, Days, NaiveTime, TimeDelta, Weekday};
+use std::collections::HashMap;
+
+/// Fewer weekly occurrences are left as separate events.
+const MIN_OCCURRENCES: usize = 3;
+/// Longer gaps split a series in two, e.g. between semesters, rather than
+/// excluding every week in between.
+const MAX_GAP: Days = Days::new(4 * 7);
+
+/// Identical events repeating every week, exported as one event with an
+/// `RRULE`.
+#[derive(Debug, Clone)]
+pub struct Series<'a> {
+    /// First occurrence, whose details and duration the series shares.
+    pub event: &'a GetCalendarResponseElement,
+    /// Start of the last weekly occurrence.
+    pub until: CyuDateTime,
+    /// Weeks between the first and last occurrences without the event,
+    /// `EXDATE`.
+    pub exdates: Vec<CyuDateTime>,
+    /// Occurrences moved to another day of the week, `RDATE`.
+    pub rdates: Vec<CyuDateTime>,
+}
+
+impl Series<'_> {
+    pub fn start(&self) -> &CyuDateTime {
+        self.event.start()
+    }
+
+    pub fn duration(&self) -> TimeDelta {
+        self.event
+            .end()
+            .as_ref()
+            .map_or(TimeDelta::zero(), |end| **end - **self.start())
+    }
+
+    /// Starts of every occurrence, sorted, as a calendar client expands
+    /// them.
+    pub fn occurrences(&self) -> Vec<CyuDateTime> {
+        let mut occurrences = std::iter::successors(Some(**self.start()), |start| {
+            start.checked_add_days(Days::new(7))
+        })
+        .take_while(|start| *start <= *self.until)
+        .map(CyuDateTime::from)
+        .filter(|start| !self.exdates.contains(start))
+        .chain(self.rdates.iter().cloned())
+        .collect::<Vec<_>>();
+        occurrences.sort();
+        occurrences
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Recurrence<'a> {
+    Single(&'a GetCalendarResponseElement),
+    Weekly(Series<'a>),
+}
+
+impl Recurrence<'_> {
+    pub fn event(&self) -> &GetCalendarResponseElement {
+        match self {
+            Self::Single(event) => event,
+            Self::Weekly(series) => series.event,
+        }
+    }
+}
+
+/// What occurrences of a series share: everything but the id and the date.
+#[derive(PartialEq, Eq, Hash)]
+struct SeriesKey<'a> {
+    time: NaiveTime,
+    duration: TimeDelta,
+    description: String,
+    category: &'a str,
+    department: &'a str,
+    faculty: Option<&'a str>,
+    sites: Option<&'a [String]>,
+    modules: Option<&'a [String]>,
+}
+
+impl<'a> SeriesKey<'a> {
+    /// `None` for the events that cannot repeat: all-day ones and the ones
+    /// without an end.
+    fn of(event: &'a GetCalendarResponseElement) -> Option<Self> {
+        if *event.all_day() {
+            return None;
+        }
+        let duration = **event.end().as_ref()? - **event.start();
+        if duration <= TimeDelta::zero() {
+            return None;
+        }
+        Some(Self {
+            time: event.start().time(),
+            duration,
+            description: event.description(),
+            category: event.event_category().as_str(),
+            department: event.department(),
+            faculty: event.faculty().as_deref(),
+            sites: event.sites().as_deref(),
+            modules: event.modules().as_deref(),
+        })
+    }
+}
+
+/// Splits occurrences on the same day of the week, sorted, into weekly
+/// series. Too short runs and duplicates are left in `singles`.
+fn weekly_runs<'a>(
+    occurrences: Vec<&'a GetCalendarResponseElement>,
+    singles: &mut Vec<Recurrence<'a>>,
+) -> Vec<Series<'a>> {
+    let mut runs: Vec<Vec<&GetCalendarResponseElement>> = Vec::new();
+    for event in occurrences {
+        let date = event.start().date();
+        let last = runs
+            .last()
+            .and_then(|run| run.last())
+            .map(|last| last.start().date());
+        match last {
+            Some(last) if last == date => singles.push(Recurrence::Single(event)),
+            Some(last)
+                if last
+                    .checked_add_days(MAX_GAP)
+                    .is_some_and(|max| date <= max) =>
+            {
+                runs.last_mut().unwrap().push(event)
+            }
+            _ => runs.push(vec![event]),
+        }
+    }
+
+    let mut series = Vec::new();
+    for run in runs {
+        if run.len() < MIN_OCCURRENCES {
+            singles.extend(run.into_iter().map(Recurrence::Single));
+            continue;
+        }
+        let (first, last) = (run[0], run[run.len() - 1]);
+        let exdates = std::iter::successors(Some(**first.start()), |start| {
+            start.checked_add_days(Days::new(7))
+        })
+        .take_while(|start| start < &**last.start())
+        .filter(|start| !run.iter().any(|event| **event.start() == *start))
+        .map(CyuDateTime::from)
+        .collect();
+        series.push(Series {
+            event: first,
+            until: last.start().clone(),
+            exdates,
+            rdates: Vec::new(),
+        });
+    }
+    series
+}
+
+/// Gathers `events` that repeat every week at the same time and place into
+/// series, the other ones are left as they are.
+///
+/// Occurrences moved to another day of the week are kept in the series they
+/// interrupt as `rdates`. Expanding the result with
+/// [`Series::occurrences`] gives back the times of `events`.
+pub fn collapse_weekly<'a>(
+    events: impl IntoIterator<Item = &'a GetCalendarResponseElement>,
+) -> Vec<Recurrence<'a>> {
+    let mut singles = Vec::new();
+    let mut groups: HashMap<SeriesKey, Vec<&GetCalendarResponseElement>> = HashMap::new();
+    for event in events {
+        match SeriesKey::of(event) {
+            Some(key) => groups.entry(key).or_default().push(event),
+            None => singles.push(Recurrence::Single(event)),
+        }
+    }
+
+    let mut series = Vec::new();
+    for mut group in groups.into_values() {
+        group.sort_by(|a, b| (a.start(), a.id()).cmp(&(b.start(), b.id())));
+        // The series are on the day most occurrences take place on
+        let mut counts: HashMap<Weekday, usize> = HashMap::new();
+        for event in &group {
+            *counts.entry(event.start().weekday()).or_default() += 1;
+        }
+        let Some(weekday) = counts
+            .into_iter()
+            .max_by_key(|(weekday, count)| {
+                (*count, std::cmp::Reverse(weekday.num_days_from_monday()))
+            })
+            .map(|(weekday, _)| weekday)
+        else {
+            continue;
+        };
+        let (weekly, moved): (Vec<_>, Vec<_>) = group
+            .into_iter()
+            .partition(|event| event.start().weekday() == weekday);
+
+        let mut runs = weekly_runs(weekly, &mut singles);
+        for event in moved {
+            let date = event.start().date();
+            let run = runs.iter_mut().find(|run| {
+                run.start().date() <= date
+                    && run
+                        .until
+                        .date()
+                        .checked_add_days(Days::new(6))
+                        .is_some_and(|last| date <= last)
+            });
+            match run {
+                Some(run) => run.rdates.push(event.start().clone()),
+                None => singles.push(Recurrence::Single(event)),
+            }
+        }
+        series.extend(runs.into_iter().map(Recurrence::Weekly));
+    }
+
+    let mut recurrences = singles;
+    recurrences.extend(series);
+    recurrences.sort_by(|a, b| {
+        let (a, b) = (a.event(), b.event());
+        (a.start(), a.id()).cmp(&(b.start(), b.id()))
+    });
+    recurrences
+}
@@ -0,0 +1,177 @@
+use chrono::{
+    DateTime, Datelike as _, Days, NaiveDate, NaiveDateTime, Offset as _, TimeDelta, Utc, Weekday,
+};
+use chrono_tz::{OffsetName as _, Tz};
+use std::ops::RangeInclusive;
+
+/// Offset from UTC in force at `instant`, in seconds, and its abbreviation.
+fn offset_at(timezone: Tz, instant: DateTime<Utc>) -> (i32, Option<String>) {
+    let offset = *instant.with_timezone(&timezone).offset();
+    (
+        offset.fix().local_minus_utc(),
+        offset.abbreviation().map(str::to_owned),
+    )
+}
+
+/// `+0100` for an hour ahead of UTC.
+fn format_offset(seconds: i32) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let minutes = seconds.abs() / 60;
+    format!("{sign}{:02}{:02}", minutes / 60, minutes % 60)
+}
+
+fn weekday_code(weekday: Weekday) -> &'static str {
+    ["MO", "TU", "WE", "TH", "FR", "SA", "SU"][weekday.num_days_from_monday() as usize]
+}
+
+/// A change of offset, e.g. to summer time.
+#[derive(Clone)]
+struct Transition {
+    /// Wall-clock time it happens at, before the change.
+    onset: NaiveDateTime,
+    from: i32,
+    to: i32,
+    name: Option<String>,
+}
+
+impl Transition {
+    /// Which weekday of the month it happens on, `-1` for the last one.
+    fn nth_weekday(&self) -> (i32, Weekday) {
+        let date = self.onset.date();
+        let is_last = date
+            .checked_add_days(Days::new(7))
+            .is_none_or(|week_after| week_after.month() != date.month());
+        let nth = if is_last {
+            -1
+        } else {
+            date.day0() as i32 / 7 + 1
+        };
+        (nth, date.weekday())
+    }
+
+    /// `BYDAY` of the yearly rule, e.g. `-1SU` for the last Sunday.
+    fn by_day(&self) -> String {
+        let (nth, weekday) = self.nth_weekday();
+        format!("{nth}{}", weekday_code(weekday))
+    }
+
+    /// Whether `other` is the same change on the same day of a month, at
+    /// the same time.
+    fn same_rule(&self, other: &Transition) -> bool {
+        (self.from, self.to, &self.name) == (other.from, other.to, &other.name)
+            && self.onset.month() == other.onset.month()
+            && self.onset.time() == other.onset.time()
+            && self.nth_weekday() == other.nth_weekday()
+    }
+}
+
+fn new_year(year: i32) -> DateTime<Utc> {
+    NaiveDate::from_ymd_opt(year, 1, 1)
+        .unwrap_or_default()
+        .and_time(chrono::NaiveTime::MIN)
+        .and_utc()
+}
+
+/// Changes of offset of `timezone` during `year`, to the second.
+fn transitions(timezone: Tz, year: i32) -> Vec<Transition> {
+    let days = std::iter::successors(Some(new_year(year)), |instant| {
+        Some(*instant + TimeDelta::days(1)).filter(|next| next.year() == year)
+    })
+    .chain([new_year(year + 1)]);
+    let mut transitions = Vec::new();
+    let mut previous: Option<(DateTime<Utc>, i32)> = None;
+    for instant in days {
+        let (offset, _) = offset_at(timezone, instant);
+        if let Some((mut before, from)) = previous.filter(|(_, from)| *from != offset) {
+            // Offsets change at most once a day, look for when by halves
+            let mut after = instant;
+            while after - before > TimeDelta::seconds(1) {
+                let middle = before + (after - before) / 2;
+                match offset_at(timezone, middle).0 == from {
+                    true => before = middle,
+                    false => after = middle,
+                }
+            }
+            transitions.push(Transition {
+                onset: after.naive_utc() + TimeDelta::seconds(from.into()),
+                from,
+                to: offset,
+                name: offset_at(timezone, after).1,
+            });
+        }
+        previous = Some((instant, offset));
+    }
+    transitions
+}
+
+/// Transitions following one rule in consecutive years.
+struct Observance {
+    first: Transition,
+    last: Transition,
+}
+
+/// `VTIMEZONE` of `timezone`, with the rules in force during `years`.
+///
+/// Each rule is given as a yearly `RRULE` from its first change in `years`,
+/// until its last one unless it is still in force at the end of `years`.
+/// Changes that do not repeat on a given weekday of a month are given as
+/// observances of their own.
+pub(super) fn vtimezone(timezone: Tz, years: RangeInclusive<i32>) -> Vec<String> {
+    let last_year = *years.end();
+    let mut observances: Vec<Observance> = Vec::new();
+    for year in years.clone() {
+        for transition in transitions(timezone, year) {
+            let ongoing = observances.iter_mut().find(|observance| {
+                observance.last.onset.year() == year - 1 && observance.last.same_rule(&transition)
+            });
+            match ongoing {
+                Some(observance) => observance.last = transition,
+                None => observances.push(Observance {
+                    first: transition.clone(),
+                    last: transition,
+                }),
+            }
+        }
+    }
+
+    let mut lines = vec![
+        String::from("BEGIN:VTIMEZONE"),
+        format!("TZID:{}", timezone.name()),
+    ];
+    if observances.is_empty() {
+        let (offset, name) = offset_at(timezone, new_year(*years.start()));
+        lines.push(String::from("BEGIN:STANDARD"));
+        lines.push(format!("TZOFFSETFROM:{}", format_offset(offset)));
+        lines.push(format!("TZOFFSETTO:{}", format_offset(offset)));
+        lines.extend(name.map(|name| format!("TZNAME:{name}")));
+        lines.push(String::from("DTSTART:19700101T000000"));
+        lines.push(String::from("END:STANDARD"));
+    }
+    observances.sort_by_key(|observance| observance.first.onset);
+    for Observance { first, last } in observances {
+        let component = match first.to > first.from {
+            true => "DAYLIGHT",
+            false => "STANDARD",
+        };
+        lines.push(format!("BEGIN:{component}"));
+        lines.push(format!("TZOFFSETFROM:{}", format_offset(first.from)));
+        lines.push(format!("TZOFFSETTO:{}", format_offset(first.to)));
+        lines.extend(first.name.as_ref().map(|name| format!("TZNAME:{name}")));
+        lines.push(format!("DTSTART:{}", first.onset.format("%Y%m%dT%H%M%S")));
+        let rule = format!(
+            "RRULE:FREQ=YEARLY;BYMONTH={};BYDAY={}",
+            first.onset.month(),
+            first.by_day()
+        );
+        if last.onset.year() == last_year {
+            lines.push(rule);
+        } else if last.onset != first.onset {
+            // UNTIL is in UTC in the rules of a timezone
+            let until = last.onset - TimeDelta::seconds(last.from.into());
+            lines.push(format!("{rule};UNTIL={}Z", until.format("%Y%m%dT%H%M%S")));
+        }
+        lines.push(format!("END:{component}"));
+    }
+    lines.push(String::from("END:VTIMEZONE"));
+    lines
+}
@@ -1,51 +1,494 @@
 pub mod auth;
+mod backend;
+mod cache;
 pub mod calendar;
+mod cookies;
 pub mod errors;
+pub mod ics;
+pub mod profile;
+pub mod scrape;
+mod session;
+pub mod sites;
 pub mod utils;
 
+pub use auth::{AuthStrategy, CasLogin, LdapLogin};
+pub use cache::CacheConfig;
+pub use cookies::SessionCookies;
 pub use errors::Error;
+pub use profile::InstanceProfile;
+pub use session::{CredentialProvider, Credentials, Identity, Session};
+
+use backend::Backend;
+use cache::{Cacheable, CalendarKey, FetcherCache, Lookup, Store};
+use futures::future::BoxFuture;
+use reqwest::Url;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const DEFAULT_BASE_URL: &str = "https://services-web.cyu.fr/calendar";
+
+/// Parses the root of a Celcat calendar, an HTTP(S) URL whose trailing
+/// slash is dropped.
+pub(crate) fn parse_base_url(base_url: &str) -> Result<Url, Error> {
+    let invalid = |reason: String| Error::BaseUrl {
+        url: base_url.to_owned(),
+        reason,
+    };
+    let mut url = Url::parse(base_url).map_err(|err| invalid(err.to_string()))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(invalid(String::from("not an HTTP URL")));
+    }
+    let path = url.path().trim_end_matches('/').to_owned();
+    url.set_path(&path);
+    Ok(url)
+}
 
 #[derive(Clone)]
 pub struct Fetcher {
     pub requester: reqwest::Client,
+    base_url: Url,
+    auth: Arc<dyn AuthStrategy>,
+    timezone: chrono_tz::Tz,
+    cache: Option<Arc<FetcherCache>>,
 }
 
 impl Fetcher {
     pub fn new() -> Self {
-        Self {
-            requester: reqwest::Client::builder()
-                .redirect(reqwest::redirect::Policy::none())
-                .build()
-                .unwrap(),
+        Self::builder().build().unwrap()
+    }
+
+    pub fn builder() -> FetcherBuilder {
+        FetcherBuilder::default()
+    }
+
+    /// Timezone of the Celcat instance, which its dates are in.
+    pub fn timezone(&self) -> chrono_tz::Tz {
+        self.timezone
+    }
+
+    /// Root of the Celcat calendar, without trailing slash.
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    /// [`Fetcher::base_url`] as the free functions take it, the paths of
+    /// the endpoints being appended to it.
+    fn base(&self) -> &str {
+        self.base_url.as_str().trim_end_matches('/')
+    }
+
+    pub async fn login(&self, username: String, password: String) -> Result<SessionCookies, Error> {
+        auth::login(
+            &self.requester,
+            self.base(),
+            self.auth.as_ref(),
+            username,
+            password,
+        )
+        .await
+    }
+
+    pub async fn get_infos(&self, token: &SessionCookies) -> Result<auth::InfosResponse, Error> {
+        let infos = auth::get_infos(&self.requester, self.base(), token).await?;
+        if let Some(cache) = &self.cache {
+            cache
+                .sessions
+                .insert(&cache.config, token, infos.federation_id.clone());
+        }
+        Ok(infos)
+    }
+
+    /// Federation id `token` belongs to, which cache entries are bound to.
+    /// `None` when CYU cannot tell, in which case the cache is bypassed.
+    async fn session_owner(&self, cache: &FetcherCache, token: &SessionCookies) -> Option<String> {
+        if let Some(owner) = cache.sessions.owner(&cache.config, token) {
+            return Some(owner);
         }
+        let infos = self.get_infos(token).await.ok()?;
+        Some(infos.federation_id)
     }
 
-    pub async fn login(&self, username: String, password: String) -> Result<String, Error> {
-        auth::login(&self.requester, username, password).await
+    /// See [`auth::get_profile`], with the calendar going through the cache
+    /// and today taken in the timezone of the instance.
+    pub async fn get_profile(
+        &self,
+        token: &SessionCookies,
+        date: Option<utils::CyuDate>,
+    ) -> Result<auth::StudentProfile, Error> {
+        auth::get_profile_with(self, token, date).await
     }
 
-    pub async fn get_infos(&self, token: String) -> Result<auth::InfosResponse, Error> {
-        auth::get_infos(&self.requester, token).await
+    pub async fn search_resources(
+        &self,
+        token: &SessionCookies,
+        kind: calendar::ResourceType,
+        query: &str,
+    ) -> Result<Vec<calendar::Resource>, Error> {
+        calendar::search_resources(&self.requester, self.base(), token, kind, query).await
     }
 
     pub async fn get_calendar(
         &self,
         query: calendar::GetCalendarQuery,
     ) -> Result<calendar::GetCalendarResponse, Error> {
-        calendar::get_calendar(&self.requester, query).await
+        let token = query.token.clone();
+        let key = CalendarKey {
+            id: query.id.clone(),
+            resource_type: query.resource_type,
+            start: query.start.clone(),
+            end: query.end.clone(),
+            color_by: query.color_by,
+        };
+        self.cached(
+            &token,
+            |cache| &cache.calendars,
+            key,
+            |fetcher| async move {
+                calendar::get_calendar(&fetcher.requester, fetcher.base(), query).await
+            },
+        )
+        .await
+    }
+
+    /// Serves `key` from `store` when fresh. Otherwise `fetch` is awaited
+    /// and its response cached, or spawned in the background when a stale
+    /// entry can be served meanwhile. Sessions CYU cannot tell the owner of
+    /// bypass the cache.
+    async fn cached<K, V, T, Fut>(
+        &self,
+        token: &SessionCookies,
+        store: fn(&FetcherCache) -> &Store<K, V>,
+        key: K,
+        fetch: impl FnOnce(Fetcher) -> Fut,
+    ) -> Result<T, Error>
+    where
+        K: Eq + Hash + Send + 'static,
+        V: Clone + Send + 'static,
+        T: Cacheable<V> + Send + 'static,
+        Fut: Future<Output = Result<T, Error>> + Send + 'static,
+    {
+        let Some(cache) = &self.cache else {
+            return fetch(self.clone()).await;
+        };
+        let Some(owner) = self.session_owner(cache, token).await else {
+            return fetch(self.clone()).await;
+        };
+
+        let stale = match store(cache).get(&cache.config, &key, &owner) {
+            Lookup::Hit(value) => return Ok(T::from_cached(value)),
+            Lookup::Revalidate(value) => Some(value),
+            Lookup::Miss => None,
+        };
+        let request = fetch_into(cache.clone(), store, key, owner, fetch(self.clone()));
+        match (stale, cache::background_runtime()) {
+            (Some(value), Some(runtime)) => {
+                runtime.spawn(async move {
+                    let _ = request.await;
+                });
+                Ok(T::from_cached(value))
+            }
+            _ => request.await,
+        }
+    }
+
+    /// Merged calendar of several timetables, each going through the cache
+    /// like [`Fetcher::get_calendar`].
+    pub async fn get_merged_calendar(
+        &self,
+        query: calendar::GetMergedCalendarQuery,
+    ) -> Result<calendar::GetMergedCalendarResponse, Error> {
+        calendar::get_merged_calendar_with(self, query).await
     }
 
     pub async fn get_calendar_limits(
         &self,
         query: calendar::GetLimitsQuery<'_>,
     ) -> Result<calendar::GetLimitsResponse, Error> {
-        calendar::get_limits(&self.requester, query).await
+        calendar::get_limits_with(self, query).await
+    }
+
+    pub async fn get_academic_years(
+        &self,
+        query: calendar::GetLimitsQuery<'_>,
+    ) -> Result<Vec<utils::AcademicYear>, Error> {
+        // The years exposed depend on the date the month page is opened at
+        let date = query
+            .date
+            .clone()
+            .unwrap_or_else(|| utils::CyuDate::today_in(self.timezone));
+        let (id, resource_type, token) = (
+            query.id.to_owned(),
+            query.resource_type,
+            query.token.clone(),
+        );
+        let key = (id.clone(), resource_type, date.clone());
+        self.cached(
+            query.token,
+            |cache| &cache.academic_years,
+            key,
+            |fetcher| async move {
+                let query = calendar::GetLimitsQuery {
+                    id: &id,
+                    resource_type,
+                    token: &token,
+                    date: Some(date),
+                };
+                calendar::get_academic_years(
+                    &fetcher.requester,
+                    fetcher.base(),
+                    fetcher.timezone,
+                    query,
+                )
+                .await
+            },
+        )
+        .await
+    }
+
+    /// Only complete responses are cached, so a range with failed chunks is
+    /// requested again on the next call.
+    pub async fn get_calendar_range(
+        &self,
+        query: calendar::GetRangeQuery,
+    ) -> Result<calendar::GetRangeResponse, Error> {
+        let token = query.token.clone();
+        let key = CalendarKey {
+            id: query.id.clone(),
+            resource_type: query.resource_type,
+            start: query.start.clone(),
+            end: query.end.clone(),
+            color_by: query.color_by,
+        };
+        self.cached(
+            &token,
+            |cache| &cache.calendars,
+            key,
+            |fetcher| async move {
+                calendar::get_range(&fetcher.requester, fetcher.base(), query).await
+            },
+        )
+        .await
     }
 
+    /// Fetches the whole current academic year, see
+    /// [`Fetcher::get_calendar_limits`] and [`Fetcher::get_calendar_range`].
     pub async fn get_all_calendar(
         &self,
         query: calendar::GetAllQuery,
-    ) -> Result<calendar::GetCalendarResponse, Error> {
-        calendar::get_all(&self.requester, query).await
+    ) -> Result<calendar::GetRangeResponse, Error> {
+        calendar::get_all_with(self, query).await
+    }
+
+    /// Forgets everything cached for `federation_id`, e.g. after a logout or
+    /// when the user asks for a refresh.
+    pub fn invalidate_cache(&self, federation_id: &str) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate(federation_id);
+        }
+    }
+
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
+    }
+}
+
+/// Awaits `request` and caches its response in `store`, see
+/// [`Store::fetch_failed`] for failures.
+async fn fetch_into<K, V, T>(
+    cache: Arc<FetcherCache>,
+    store: fn(&FetcherCache) -> &Store<K, V>,
+    key: K,
+    owner: String,
+    request: impl Future<Output = Result<T, Error>>,
+) -> Result<T, Error>
+where
+    K: Eq + Hash,
+    V: Clone,
+    T: Cacheable<V>,
+{
+    let store = store(&cache);
+    match request.await {
+        Ok(response) => {
+            match response.to_cached() {
+                Some(value) => store.insert(&cache.config, key, owner, value),
+                None => store.fetch_failed(&key, false),
+            }
+            Ok(response)
+        }
+        Err(error) => {
+            store.fetch_failed(&key, matches!(error, Error::Unauthorized));
+            Err(error)
+        }
+    }
+}
+
+impl Backend for Fetcher {
+    fn today(&self) -> utils::CyuDate {
+        utils::CyuDate::today_in(self.timezone)
+    }
+
+    fn get_infos<'a>(
+        &'a self,
+        token: &'a SessionCookies,
+    ) -> BoxFuture<'a, Result<auth::InfosResponse, Error>> {
+        Box::pin(Fetcher::get_infos(self, token))
+    }
+
+    fn get_calendar(
+        &self,
+        query: calendar::GetCalendarQuery,
+    ) -> BoxFuture<'_, Result<calendar::GetCalendarResponse, Error>> {
+        Box::pin(Fetcher::get_calendar(self, query))
+    }
+
+    fn get_academic_years<'a>(
+        &'a self,
+        query: calendar::GetLimitsQuery<'a>,
+    ) -> BoxFuture<'a, Result<Vec<utils::AcademicYear>, Error>> {
+        Box::pin(Fetcher::get_academic_years(self, query))
+    }
+
+    fn get_range(
+        &self,
+        query: calendar::GetRangeQuery,
+    ) -> BoxFuture<'_, Result<calendar::GetRangeResponse, Error>> {
+        Box::pin(self.get_calendar_range(query))
+    }
+}
+
+impl Default for Fetcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Configures the HTTP side of a [`Fetcher`].
+///
+/// A caller-supplied client is used as is, so it must not follow redirects:
+/// login and session checks rely on seeing the raw `302` responses.
+#[derive(Default)]
+pub struct FetcherBuilder {
+    base_url: Option<String>,
+    user_agent: Option<String>,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    proxies: Vec<reqwest::Proxy>,
+    no_proxy: bool,
+    client: Option<reqwest::Client>,
+    auth: Option<Arc<dyn AuthStrategy>>,
+    timezone: Option<chrono_tz::Tz>,
+    cache: Option<CacheConfig>,
+}
+
+impl FetcherBuilder {
+    /// Root of the Celcat calendar, without trailing slash.
+    /// Defaults to [`DEFAULT_BASE_URL`].
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    pub fn proxy(mut self, proxy: reqwest::Proxy) -> Self {
+        self.proxies.push(proxy);
+        self
+    }
+
+    /// Ignore proxies set through the environment (`HTTP_PROXY`, ...).
+    pub fn no_proxy(mut self) -> Self {
+        self.no_proxy = true;
+        self
+    }
+
+    /// Use this client instead of building one. Timeouts, proxies and
+    /// user agent set on the builder are ignored in that case.
+    pub fn client(mut self, client: reqwest::Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// How to log in to the calendar. Defaults to its own LDAP form,
+    /// [`LdapLogin`].
+    pub fn auth_strategy(mut self, strategy: impl AuthStrategy + 'static) -> Self {
+        self.auth = Some(Arc::new(strategy));
+        self
+    }
+
+    /// Timezone of the instance. Defaults to [`utils::TIMEZONE`].
+    pub fn timezone(mut self, timezone: chrono_tz::Tz) -> Self {
+        self.timezone = Some(timezone);
+        self
+    }
+
+    /// Targets the Celcat instance of `profile`: its base URL, auth strategy
+    /// and timezone. Its sites are for the frontends to use.
+    pub fn profile(mut self, profile: &InstanceProfile) -> Self {
+        self.base_url = Some(profile.base_url.to_string());
+        self.auth = Some(profile.auth.strategy());
+        self.timezone = Some(profile.timezone);
+        self
+    }
+
+    /// Keep calendar data in memory, keyed by federation id and date range.
+    /// Entries are shared by the sessions of the user who fetched them, which
+    /// costs a look at the home page the first time a session is seen.
+    /// Disabled by default.
+    pub fn cache(mut self, config: CacheConfig) -> Self {
+        self.cache = Some(config);
+        self
+    }
+
+    pub fn build(self) -> Result<Fetcher, Error> {
+        let base_url = parse_base_url(self.base_url.as_deref().unwrap_or(DEFAULT_BASE_URL))?;
+
+        let requester = match self.client {
+            Some(client) => client,
+            None => {
+                let mut builder =
+                    reqwest::Client::builder().redirect(reqwest::redirect::Policy::none());
+                if let Some(user_agent) = self.user_agent {
+                    builder = builder.user_agent(user_agent);
+                }
+                if let Some(timeout) = self.timeout {
+                    builder = builder.timeout(timeout);
+                }
+                if let Some(timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(timeout);
+                }
+                if self.no_proxy {
+                    builder = builder.no_proxy();
+                }
+                for proxy in self.proxies {
+                    builder = builder.proxy(proxy);
+                }
+                builder.build().map_err(Error::Client)?
+            }
+        };
+
+        Ok(Fetcher {
+            requester,
+            base_url,
+            auth: self.auth.unwrap_or_else(|| Arc::new(LdapLogin)),
+            timezone: self.timezone.unwrap_or(utils::TIMEZONE),
+            cache: self.cache.map(|config| Arc::new(FetcherCache::new(config))),
+        })
     }
 }
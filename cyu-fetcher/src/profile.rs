@@ -0,0 +1,175 @@
+use crate::auth::{AuthStrategy, CasLogin, LdapLogin};
+use crate::ics::IcsOptions;
+use crate::sites::SiteRegistry;
+use crate::utils::TIMEZONE;
+use crate::{parse_base_url, DEFAULT_BASE_URL};
+use once_cell::sync::Lazy;
+use reqwest::Url;
+use serde::{Deserialize, Deserializer};
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// How an instance logs its users in.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum AuthConfig {
+    /// The LDAP form of Celcat, see [`LdapLogin`].
+    #[default]
+    Ldap,
+    /// A CAS server, see [`CasLogin`].
+    Cas {
+        server_url: String,
+        /// Defaults to `/CasLogin`.
+        #[serde(default)]
+        service_path: Option<String>,
+    },
+}
+
+impl AuthConfig {
+    pub fn strategy(&self) -> Arc<dyn AuthStrategy> {
+        match self {
+            Self::Ldap => Arc::new(LdapLogin),
+            Self::Cas {
+                server_url,
+                service_path,
+            } => {
+                let mut strategy = CasLogin::new(server_url.as_str());
+                if let Some(service_path) = service_path {
+                    strategy = strategy.service_path(service_path.as_str());
+                }
+                Arc::new(strategy)
+            }
+        }
+    }
+}
+
+fn deserialize_timezone<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<chrono_tz::Tz, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    name.parse()
+        .map_err(|_| serde::de::Error::custom(format!("unknown timezone {name:?}")))
+}
+
+fn deserialize_base_url<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Url, D::Error> {
+    let base_url = String::deserialize(deserializer)?;
+    parse_base_url(&base_url).map_err(serde::de::Error::custom)
+}
+
+const fn default_timezone() -> chrono_tz::Tz {
+    TIMEZONE
+}
+
+#[derive(Debug)]
+pub enum ProfileError {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Toml(toml::de::Error),
+}
+
+impl Display for ProfileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "failed to read {}: {source}", path.display()),
+            Self::Toml(err) => write!(f, "invalid profile: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ProfileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Toml(err) => Some(err),
+        }
+    }
+}
+
+/// A Celcat Calendar deployment: where it is, how to log in and where its
+/// events take place.
+///
+/// CYU is the default, see [`InstanceProfile::cyu`]. Other universities
+/// describe theirs in a TOML file:
+///
+/// ```toml
+/// name = "Université Exemple"
+/// base_url = "https://edt.example.fr/calendar"
+/// timezone = "Europe/Paris"
+///
+/// [auth]
+/// kind = "cas"
+/// server_url = "https://cas.example.fr/cas"
+///
+/// [[sites]]
+/// name = "CAMPUS"
+/// coordinates = [48.85, 2.35]
+/// ```
+///
+/// Only `name` and `base_url` are required. The LDAP form of Celcat is used
+/// by default, in the CYU timezone, and events are not located.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct InstanceProfile {
+    /// Short name of the university, e.g. in calendar names.
+    pub name: String,
+    /// Root of the Celcat calendar, see
+    /// [`FetcherBuilder::base_url`](crate::FetcherBuilder::base_url).
+    #[serde(deserialize_with = "deserialize_base_url")]
+    pub base_url: Url,
+    #[serde(default)]
+    pub auth: AuthConfig,
+    /// Timezone Celcat gives dates and times in.
+    #[serde(
+        default = "default_timezone",
+        deserialize_with = "deserialize_timezone"
+    )]
+    pub timezone: chrono_tz::Tz,
+    #[serde(flatten)]
+    pub sites: SiteRegistry,
+}
+
+impl InstanceProfile {
+    /// CY Cergy Paris Université, with the embedded sites.
+    pub fn cyu() -> &'static Self {
+        static CYU: Lazy<InstanceProfile> = Lazy::new(|| InstanceProfile {
+            name: String::from("CYU"),
+            base_url: parse_base_url(DEFAULT_BASE_URL).expect("CYU URL is valid"),
+            auth: AuthConfig::Ldap,
+            timezone: TIMEZONE,
+            sites: SiteRegistry::embedded().clone(),
+        });
+        &CYU
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self, ProfileError> {
+        toml::from_str(content).map_err(ProfileError::Toml)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ProfileError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|source| ProfileError::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+        Self::from_toml(&content)
+    }
+
+    /// Export options for this instance: named after it, in its timezone
+    /// and locating events with its sites.
+    pub fn ics_options(&self) -> IcsOptions<'_> {
+        IcsOptions {
+            calendar_name: format!("{} Calendar", self.name),
+            sites: Some(&self.sites),
+            timezone: self.timezone,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for InstanceProfile {
+    fn default() -> Self {
+        Self::cyu().clone()
+    }
+}
@@ -0,0 +1,197 @@
+use crate::errors::Error;
+use crate::utils::CyuDate;
+use chrono::NaiveDate;
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use scraper::{Html, Selector};
+
+/// Length of the page excerpt given with layout errors, in bytes.
+const FRAGMENT_LENGTH: usize = 400;
+
+/// Part of `page` around the first of `hints` it contains, or its start,
+/// to see what the page looks like now.
+fn fragment(page: &str, hints: &[&str]) -> String {
+    let floor = |mut index: usize| {
+        while !page.is_char_boundary(index) {
+            index -= 1;
+        }
+        index
+    };
+    let start = hints
+        .iter()
+        .find_map(|hint| page.find(hint))
+        .map_or(0, |index| floor(index.saturating_sub(FRAGMENT_LENGTH / 4)));
+    let end = floor((start + FRAGMENT_LENGTH).min(page.len()));
+    page[start..end].trim().to_owned()
+}
+
+fn layout(page: &'static str, marker: &'static str, html: &str, hints: &[&str]) -> Error {
+    Error::Layout {
+        page,
+        marker,
+        fragment: Some(fragment(html, hints)),
+    }
+}
+
+/// Contents of the inline scripts of `html`.
+fn scripts(html: &str) -> Vec<String> {
+    static SCRIPT: Lazy<Selector> = Lazy::new(|| Selector::parse("script").unwrap());
+    Html::parse_document(html)
+        .select(&SCRIPT)
+        .map(|script| script.text().collect())
+        .collect()
+}
+
+/// Value of a JS string literal captured by `regex`, in single or double
+/// quotes.
+fn js_string(captures: &Captures) -> Option<String> {
+    let literal = captures.get(1).or_else(|| captures.get(2))?.as_str();
+    let mut value = String::with_capacity(literal.len());
+    let mut chars = literal.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => value.extend(chars.next()),
+            c => value.push(c),
+        }
+    }
+    Some(value)
+}
+
+/// Anti-forgery token of the login form.
+pub fn verification_token(html: &str) -> Result<String, Error> {
+    static TOKEN_INPUT: Lazy<Selector> =
+        Lazy::new(|| Selector::parse(r#"input[name="__RequestVerificationToken"]"#).unwrap());
+    Html::parse_document(html)
+        .select(&TOKEN_INPUT)
+        .find_map(|input| input.value().attr("value"))
+        .filter(|token| !token.is_empty())
+        .map(str::to_owned)
+        .ok_or_else(|| {
+            layout(
+                "LdapLogin",
+                "__RequestVerificationToken input",
+                html,
+                &["__RequestVerificationToken", "<form"],
+            )
+        })
+}
+
+/// Whether `html` is the LDAP login page, which Celcat serves with a `200`
+/// in place of the requested page once the session expired.
+pub fn is_login_page(html: &str) -> bool {
+    verification_token(html).is_ok()
+}
+
+/// Federation id of the logged in student, set by a script of the home
+/// page.
+pub fn federation_id(html: &str) -> Result<String, Error> {
+    static FEDERATION_ID: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r#"\bfederationIdStr\s*=\s*(?:'((?:[^'\\]|\\.)*)'|"((?:[^"\\]|\\.)*)")"#)
+            .unwrap()
+    });
+    scripts(html)
+        .iter()
+        .find_map(|script| js_string(&FEDERATION_ID.captures(script)?))
+        .ok_or_else(|| {
+            layout(
+                "Home",
+                "federationIdStr variable",
+                html,
+                &["federationIdStr", "<script"],
+            )
+        })
+}
+
+/// Date of a `new Date(year, month - 1, day)` call, JS months starting
+/// at 0.
+fn js_date(captures: &Captures) -> Option<CyuDate> {
+    let number = |index| captures.get(index)?.as_str().parse::<u32>().ok();
+    let month = match captures.get(4) {
+        Some(_) => number(3)?,
+        None => number(3)? + 1,
+    };
+    NaiveDate::from_ymd_opt(number(2)?.try_into().ok()?, month, number(5)?).map(CyuDate::from)
+}
+
+/// First and last days the month view lets us browse, from the
+/// `dateExtents` object of its scripts.
+pub fn date_extents(html: &str) -> Result<(CyuDate, CyuDate), Error> {
+    static DATE_EXTENTS: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"\bdateExtents\s*=\s*\{([^}]*)\}").unwrap());
+    static EXTENT: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r#"["']?\b(earliest|latest)["']?\s*:\s*new\s+Date\s*\(\s*(\d+)\s*,\s*(\d+)\s*(-\s*1\s*)?,\s*(\d+)\s*\)"#,
+        )
+        .unwrap()
+    });
+
+    let extents = scripts(html).iter().find_map(|script| {
+        let object = DATE_EXTENTS.captures(script)?.get(1)?.as_str();
+        let (mut earliest, mut latest) = (None, None);
+        for captures in EXTENT.captures_iter(object) {
+            let date = js_date(&captures)?;
+            match &captures[1] {
+                "earliest" => earliest = Some(date),
+                _ => latest = Some(date),
+            }
+        }
+        Some((earliest?, latest?))
+    });
+    extents.ok_or_else(|| {
+        layout(
+            "Home (month view)",
+            "dateExtents variable",
+            html,
+            &["dateExtents", "<script"],
+        )
+    })
+}
+
+/// A login form to submit, with the hidden fields that must go along.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoginForm {
+    /// Where to post the form, relative to the page. `None` posts it to the
+    /// page itself.
+    pub action: Option<String>,
+    pub hidden_fields: Vec<(String, String)>,
+}
+
+/// The form of a CAS login page, the one with a password field.
+pub fn cas_login_form(html: &str) -> Result<LoginForm, Error> {
+    static FORM: Lazy<Selector> = Lazy::new(|| Selector::parse("form").unwrap());
+    static PASSWORD: Lazy<Selector> =
+        Lazy::new(|| Selector::parse(r#"input[name="password"]"#).unwrap());
+    static HIDDEN: Lazy<Selector> =
+        Lazy::new(|| Selector::parse(r#"input[type="hidden" i][name]"#).unwrap());
+
+    let document = Html::parse_document(html);
+    let form = document
+        .select(&FORM)
+        .find(|form| form.select(&PASSWORD).next().is_some())
+        .ok_or_else(|| {
+            layout(
+                "CAS login",
+                "login form",
+                html,
+                &["<form", "execution", "<body"],
+            )
+        })?;
+    let hidden_fields = form
+        .select(&HIDDEN)
+        .filter_map(|input| {
+            let input = input.value();
+            Some((
+                input.attr("name")?.to_owned(),
+                input.attr("value").unwrap_or_default().to_owned(),
+            ))
+        })
+        .collect();
+    Ok(LoginForm {
+        action: form
+            .value()
+            .attr("action")
+            .filter(|action| !action.is_empty())
+            .map(str::to_owned),
+        hidden_fields,
+    })
+}
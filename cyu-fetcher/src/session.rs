@@ -0,0 +1,359 @@
+use crate::auth::{InfosResponse, StudentProfile};
+use crate::calendar::{
+    CalendarSource, CalendarView, ColorBy, GetAllQuery, GetCalendarQuery, GetCalendarResponse,
+    GetLimitsQuery, GetLimitsResponse, GetMergedCalendarQuery, GetMergedCalendarResponse,
+    GetRangeQuery, GetRangeResponse, RangeOptions, Resource, ResourceType,
+};
+use crate::cookies::SessionCookies;
+use crate::errors::Error;
+use crate::utils::{AcademicYear, CyuDate};
+use crate::Fetcher;
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::future::Future;
+use tokio::sync::Mutex;
+
+/// Username and password of a CYU account.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+impl Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credentials")
+            .field("username", &self.username)
+            .field("password", &"***")
+            .finish()
+    }
+}
+
+/// Where a [`Session`] gets credentials from when it has to log in.
+pub trait CredentialProvider: Send + Sync {
+    /// `None` when there are no credentials to log in with, which the
+    /// session reports as [`Error::Unauthorized`].
+    fn credentials(&self) -> BoxFuture<'_, Option<Credentials>>;
+}
+
+impl CredentialProvider for Credentials {
+    fn credentials(&self) -> BoxFuture<'_, Option<Credentials>> {
+        Box::pin(std::future::ready(Some(self.clone())))
+    }
+}
+
+/// What CYU needs to know who is asking.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Identity {
+    /// Cookies of the CYU session.
+    pub token: SessionCookies,
+    pub federation_id: String,
+}
+
+struct State {
+    identity: Identity,
+    infos: Option<InfosResponse>,
+}
+
+/// A logged in user, whose CYU session is renewed when it expires.
+///
+/// The session logs in on first use, asking the [`CredentialProvider`] for
+/// credentials. Whenever CYU answers [`Error::Unauthorized`], it logs in
+/// again and retries once, so callers only see that error when the
+/// credentials themselves are refused.
+///
+/// Every call sends the cookies of the current identity, so the cookies
+/// CYU renews along the way are used by the next calls.
+pub struct Session {
+    fetcher: Fetcher,
+    credentials: Box<dyn CredentialProvider>,
+    state: Mutex<Option<State>>,
+}
+
+impl Session {
+    pub fn new(fetcher: Fetcher, credentials: impl CredentialProvider + 'static) -> Self {
+        Self {
+            fetcher,
+            credentials: Box::new(credentials),
+            state: Mutex::new(None),
+        }
+    }
+
+    /// Starts from an identity obtained earlier, e.g. saved by a frontend,
+    /// instead of logging in on first use.
+    pub fn resume(mut self, identity: Identity) -> Self {
+        *self.state.get_mut() = Some(State {
+            identity,
+            infos: None,
+        });
+        self
+    }
+
+    pub fn fetcher(&self) -> &Fetcher {
+        &self.fetcher
+    }
+
+    /// Current identity, logging in if needed. Its cookies are those of
+    /// the session, kept up to date with the ones CYU sets.
+    pub async fn identity(&self) -> Result<Identity, Error> {
+        let mut state = self.state.lock().await;
+        if let Some(state) = &*state {
+            return Ok(state.identity.clone());
+        }
+        let credentials = self.provided_credentials().await?;
+        self.log_in(&mut state, credentials).await
+    }
+
+    /// Logs in with the provider's credentials, dropping the current session.
+    pub async fn login(&self) -> Result<Identity, Error> {
+        let mut state = self.state.lock().await;
+        let credentials = self.provided_credentials().await?;
+        self.log_in(&mut state, credentials).await
+    }
+
+    /// Logs in with `credentials` rather than the provider's, e.g. the ones
+    /// typed in a login form before they are saved anywhere.
+    pub async fn login_with(&self, credentials: Credentials) -> Result<Identity, Error> {
+        let mut state = self.state.lock().await;
+        self.log_in(&mut state, credentials).await
+    }
+
+    pub async fn logout(&self) {
+        if let Some(state) = self.state.lock().await.take() {
+            self.fetcher.invalidate_cache(&state.identity.federation_id);
+        }
+    }
+
+    async fn provided_credentials(&self) -> Result<Credentials, Error> {
+        self.credentials
+            .credentials()
+            .await
+            .ok_or(Error::Unauthorized)
+    }
+
+    async fn log_in(
+        &self,
+        state: &mut Option<State>,
+        credentials: Credentials,
+    ) -> Result<Identity, Error> {
+        *state = None;
+        let token = self
+            .fetcher
+            .login(credentials.username, credentials.password)
+            .await?;
+        let infos = self.fetcher.get_infos(&token).await?;
+        let identity = Identity {
+            token,
+            federation_id: infos.federation_id.clone(),
+        };
+        *state = Some(State {
+            identity: identity.clone(),
+            infos: Some(infos),
+        });
+        Ok(identity)
+    }
+
+    /// Logs in again, unless another call already did since `expired` was
+    /// handed out.
+    async fn renew(&self, expired: &Identity) -> Result<Identity, Error> {
+        let mut state = self.state.lock().await;
+        if let Some(current) = &*state {
+            if !current.identity.token.same_session(&expired.token) {
+                return Ok(current.identity.clone());
+            }
+        }
+        let credentials = self.provided_credentials().await?;
+        self.log_in(&mut state, credentials).await
+    }
+
+    async fn call<T, F, Fut>(&self, call: F) -> Result<T, Error>
+    where
+        F: Fn(Identity) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let identity = self.identity().await?;
+        match call(identity.clone()).await {
+            Err(Error::Unauthorized) => call(self.renew(&identity).await?).await,
+            result => result,
+        }
+    }
+
+    pub async fn get_infos(&self) -> Result<InfosResponse, Error> {
+        if let Some(infos) = self
+            .state
+            .lock()
+            .await
+            .as_ref()
+            .and_then(|state| state.infos.clone())
+        {
+            return Ok(infos);
+        }
+        self.call(|identity| async move { self.fetcher.get_infos(&identity.token).await })
+            .await
+    }
+
+    /// See [`Fetcher::get_profile`], reading the groups from the coming
+    /// weeks.
+    pub async fn get_profile(&self) -> Result<StudentProfile, Error> {
+        self.call(|identity| async move { self.fetcher.get_profile(&identity.token, None).await })
+            .await
+    }
+
+    /// See [`Fetcher::search_resources`].
+    pub async fn search_resources(
+        &self,
+        kind: ResourceType,
+        query: &str,
+    ) -> Result<Vec<Resource>, Error> {
+        self.call(|identity| async move {
+            self.fetcher
+                .search_resources(&identity.token, kind, query)
+                .await
+        })
+        .await
+    }
+
+    /// Timetable of the logged in student.
+    pub async fn get_calendar(
+        &self,
+        start: CyuDate,
+        end: CyuDate,
+        view: CalendarView,
+        color_by: ColorBy,
+    ) -> Result<GetCalendarResponse, Error> {
+        self.call(|identity| {
+            self.fetcher.get_calendar(GetCalendarQuery {
+                id: identity.federation_id,
+                resource_type: ResourceType::Student,
+                token: identity.token,
+                start: start.clone(),
+                end: end.clone(),
+                view,
+                color_by,
+            })
+        })
+        .await
+    }
+
+    /// Timetable of a room, a teacher, a group, ... as seen by the logged in
+    /// student.
+    pub async fn get_resource_calendar(
+        &self,
+        resource_type: ResourceType,
+        id: &str,
+        start: CyuDate,
+        end: CyuDate,
+        view: CalendarView,
+        color_by: ColorBy,
+    ) -> Result<GetCalendarResponse, Error> {
+        self.call(|identity| {
+            self.fetcher.get_calendar(GetCalendarQuery {
+                id: id.to_owned(),
+                resource_type,
+                token: identity.token,
+                start: start.clone(),
+                end: end.clone(),
+                view,
+                color_by,
+            })
+        })
+        .await
+    }
+
+    /// Timetables of `sources` merged into one, e.g. the student's main
+    /// group and option groups.
+    pub async fn get_merged_calendar(
+        &self,
+        sources: &[CalendarSource],
+        start: CyuDate,
+        end: CyuDate,
+        view: CalendarView,
+        color_by: ColorBy,
+    ) -> Result<GetMergedCalendarResponse, Error> {
+        self.call(|identity| {
+            self.fetcher.get_merged_calendar(GetMergedCalendarQuery {
+                sources: sources.to_vec(),
+                token: identity.token,
+                start: start.clone(),
+                end: end.clone(),
+                view,
+                color_by,
+            })
+        })
+        .await
+    }
+
+    /// See [`Fetcher::get_calendar_limits`], `date` defaulting to today.
+    pub async fn get_calendar_limits(
+        &self,
+        date: Option<CyuDate>,
+    ) -> Result<GetLimitsResponse, Error> {
+        self.call(|identity| {
+            let date = date.clone();
+            async move {
+                self.fetcher
+                    .get_calendar_limits(GetLimitsQuery {
+                        id: &identity.federation_id,
+                        resource_type: ResourceType::Student,
+                        token: &identity.token,
+                        date,
+                    })
+                    .await
+            }
+        })
+        .await
+    }
+
+    pub async fn get_academic_years(
+        &self,
+        date: Option<CyuDate>,
+    ) -> Result<Vec<AcademicYear>, Error> {
+        self.call(|identity| {
+            let date = date.clone();
+            async move {
+                self.fetcher
+                    .get_academic_years(GetLimitsQuery {
+                        id: &identity.federation_id,
+                        resource_type: ResourceType::Student,
+                        token: &identity.token,
+                        date,
+                    })
+                    .await
+            }
+        })
+        .await
+    }
+
+    pub async fn get_calendar_range(
+        &self,
+        start: CyuDate,
+        end: CyuDate,
+        color_by: ColorBy,
+        options: RangeOptions,
+    ) -> Result<GetRangeResponse, Error> {
+        self.call(|identity| {
+            self.fetcher.get_calendar_range(GetRangeQuery {
+                id: identity.federation_id,
+                resource_type: ResourceType::Student,
+                token: identity.token,
+                start: start.clone(),
+                end: end.clone(),
+                color_by,
+                options,
+            })
+        })
+        .await
+    }
+
+    pub async fn get_all_calendar(&self, color_by: ColorBy) -> Result<GetRangeResponse, Error> {
+        self.call(|identity| {
+            self.fetcher.get_all_calendar(GetAllQuery {
+                id: identity.federation_id,
+                token: identity.token,
+                color_by,
+            })
+        })
+        .await
+    }
+}
@@ -0,0 +1,274 @@
+use crate::calendar::GetCalendarResponseElement;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_SITES: &str = include_str!("../assets/sites.toml");
+
+/// `[latitude, longitude]`
+pub type Coordinates = [f64; 2];
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Building {
+    pub name: String,
+    /// Rooms whose name starts with one of these are in this building.
+    #[serde(default)]
+    pub room_prefixes: Vec<String>,
+    pub address: Option<String>,
+    pub coordinates: Option<Coordinates>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Site {
+    /// Name as given by Celcat.
+    pub name: String,
+    /// Other spellings found in Celcat events.
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub address: Option<String>,
+    pub coordinates: Coordinates,
+    /// Rooms whose name starts with one of these are on this site.
+    #[serde(default)]
+    pub room_prefixes: Vec<String>,
+    #[serde(default)]
+    pub buildings: Vec<Building>,
+}
+
+impl Site {
+    fn is_named(&self, name: &str) -> bool {
+        let name = name.trim();
+        self.name.eq_ignore_ascii_case(name)
+            || self
+                .aliases
+                .iter()
+                .any(|alias| alias.eq_ignore_ascii_case(name))
+    }
+
+    /// Building of `room`, by the longest matching prefix.
+    fn building(&self, room: &str) -> Option<&Building> {
+        self.buildings
+            .iter()
+            .filter_map(|building| {
+                Some((building, matching_prefix(&building.room_prefixes, room)?))
+            })
+            .max_by_key(|(_, length)| *length)
+            .map(|(building, _)| building)
+    }
+}
+
+/// Length of the longest of `prefixes` `room` starts with.
+fn matching_prefix(prefixes: &[String], room: &str) -> Option<usize> {
+    prefixes
+        .iter()
+        .filter(|prefix| {
+            room.get(..prefix.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+        })
+        .map(String::len)
+        .max()
+}
+
+/// A site, and the building when the room tells which.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Location<'a> {
+    pub site: &'a Site,
+    pub building: Option<&'a Building>,
+}
+
+impl Location<'_> {
+    pub fn coordinates(&self) -> Coordinates {
+        self.building
+            .and_then(|building| building.coordinates)
+            .unwrap_or(self.site.coordinates)
+    }
+
+    pub fn address(&self) -> Option<&str> {
+        self.building
+            .and_then(|building| building.address.as_deref())
+            .or(self.site.address.as_deref())
+    }
+
+    /// Site name, followed by the building if known.
+    pub fn name(&self) -> String {
+        match self.building {
+            Some(building) => format!("{}, {}", self.site.name, building.name),
+            None => self.site.name.clone(),
+        }
+    }
+}
+
+/// A site named by an event but missing from the registry.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct UnknownSite {
+    pub name: String,
+}
+
+impl Display for UnknownSite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown site {:?}", self.name)
+    }
+}
+
+impl std::error::Error for UnknownSite {}
+
+#[derive(Debug)]
+pub enum SiteRegistryError {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Toml(toml::de::Error),
+    Json(serde_json::Error),
+    /// Only `.toml` and `.json` files can be loaded.
+    UnsupportedFormat(PathBuf),
+}
+
+impl Display for SiteRegistryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "failed to read {}: {source}", path.display()),
+            Self::Toml(err) => write!(f, "invalid sites file: {err}"),
+            Self::Json(err) => write!(f, "invalid sites file: {err}"),
+            Self::UnsupportedFormat(path) => {
+                write!(f, "{} is neither a TOML nor a JSON file", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for SiteRegistryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Toml(err) => Some(err),
+            Self::Json(err) => Some(err),
+            Self::UnsupportedFormat(_) => None,
+        }
+    }
+}
+
+/// Where events take place.
+///
+/// Celcat only gives site and room names. The registry maps them to
+/// coordinates, addresses and buildings, from a list embedded in the crate
+/// that deployments can extend with their own file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct SiteRegistry {
+    #[serde(default)]
+    sites: Vec<Site>,
+}
+
+impl SiteRegistry {
+    /// The sites embedded in the crate, see `assets/sites.toml`.
+    pub fn embedded() -> &'static Self {
+        static EMBEDDED: Lazy<SiteRegistry> = Lazy::new(|| {
+            SiteRegistry::from_toml(DEFAULT_SITES).expect("embedded sites file is valid")
+        });
+        &EMBEDDED
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self, SiteRegistryError> {
+        toml::from_str(content).map_err(SiteRegistryError::Toml)
+    }
+
+    pub fn from_json(content: &str) -> Result<Self, SiteRegistryError> {
+        serde_json::from_str(content).map_err(SiteRegistryError::Json)
+    }
+
+    /// Reads a `.toml` or `.json` file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, SiteRegistryError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|source| SiteRegistryError::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Self::from_toml(&content),
+            Some("json") => Self::from_json(&content),
+            _ => Err(SiteRegistryError::UnsupportedFormat(path.to_owned())),
+        }
+    }
+
+    /// The embedded sites, overridden by the ones of the file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SiteRegistryError> {
+        Ok(Self::embedded().clone().merge(Self::from_file(path)?))
+    }
+
+    /// Adds the sites of `other`, which replace the ones of the same name.
+    pub fn merge(mut self, other: Self) -> Self {
+        for site in other.sites {
+            match self
+                .sites
+                .iter_mut()
+                .find(|existing| existing.is_named(&site.name))
+            {
+                Some(existing) => *existing = site,
+                None => self.sites.push(site),
+            }
+        }
+        self
+    }
+
+    pub fn sites(&self) -> &[Site] {
+        &self.sites
+    }
+
+    /// Site called `name`, or one of its aliases, ignoring case.
+    pub fn site(&self, name: &str) -> Option<&Site> {
+        self.sites.iter().find(|site| site.is_named(name))
+    }
+
+    /// Location of `room`, by the longest matching site or building prefix.
+    pub fn room(&self, room: &str) -> Option<Location<'_>> {
+        let room = room.trim();
+        self.sites
+            .iter()
+            .filter_map(|site| {
+                let building = site.building(room);
+                let length = building
+                    .and_then(|building| matching_prefix(&building.room_prefixes, room))
+                    .max(matching_prefix(&site.room_prefixes, room))?;
+                Some((Location { site, building }, length))
+            })
+            .max_by_key(|(_, length)| *length)
+            .map(|(location, _)| location)
+    }
+
+    /// Where `event` takes place, from its first site and rooms.
+    ///
+    /// `Ok(None)` when the event tells nothing we can use, e.g. it has no
+    /// site and its rooms are unknown. A site missing from the registry is
+    /// an error rather than `None`, so that it can be reported and added.
+    pub fn locate(
+        &self,
+        event: &GetCalendarResponseElement,
+    ) -> Result<Option<Location<'_>>, UnknownSite> {
+        let rooms = event.details().rooms;
+        let from_rooms = rooms.iter().find_map(|room| self.room(room));
+        let Some(name) = event.sites().as_ref().and_then(|sites| sites.first()) else {
+            return Ok(from_rooms);
+        };
+        let Some(site) = self.site(name) else {
+            return from_rooms
+                .map(Some)
+                .ok_or_else(|| UnknownSite { name: name.clone() });
+        };
+        let building = rooms.iter().find_map(|room| site.building(room));
+        Ok(Some(Location { site, building }))
+    }
+
+    /// Sites named by `events` that are missing from the registry.
+    pub fn unknown_sites<'a>(
+        &self,
+        events: impl IntoIterator<Item = &'a GetCalendarResponseElement>,
+    ) -> BTreeSet<String> {
+        events
+            .into_iter()
+            .flat_map(|event| event.sites().iter().flatten())
+            .filter(|name| !name.trim().is_empty() && self.site(name).is_none())
+            .cloned()
+            .collect()
+    }
+}
@@ -1,10 +1,15 @@
+use chrono::{Datelike as _, LocalResult, Offset as _, TimeZone as _};
 use derive_more::derive::{Deref, From};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt::Display;
 
-#[derive(Debug, Default, Clone, PartialEq, Eq, Deref, From)]
+/// Timezone of every date and time returned by CYU, the default one of
+/// [`InstanceProfile`](crate::profile::InstanceProfile).
+pub const TIMEZONE: chrono_tz::Tz = chrono_tz::Europe::Paris;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Deref, From)]
 pub struct CyuDate(chrono::NaiveDate);
-#[derive(Debug, Default, Clone, PartialEq, Eq, Deref, From)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Deref, From)]
 pub struct CyuDateTime(chrono::NaiveDateTime);
 
 impl CyuDate {
@@ -12,8 +17,12 @@ impl CyuDate {
         chrono::NaiveDate::from_ymd_opt(year, month, day).map(Self::from)
     }
     pub fn today() -> Self {
+        Self::today_in(TIMEZONE)
+    }
+    /// Current date in `timezone`, that of the Celcat instance.
+    pub fn today_in(timezone: chrono_tz::Tz) -> Self {
         chrono::Utc::now()
-            .with_timezone(&chrono_tz::CET)
+            .with_timezone(&timezone)
             .date_naive()
             .into()
     }
@@ -25,14 +34,125 @@ impl CyuDateTime {
         let time = chrono::NaiveTime::from_hms_opt(hour, min, sec)?;
         Some(chrono::NaiveDateTime::new(date, time).into())
     }
+    /// Resolves this wall-clock time in [`TIMEZONE`], see
+    /// [`CyuDateTime::as_local_in`].
+    pub fn as_local(&self) -> chrono::DateTime<chrono_tz::Tz> {
+        self.as_local_in(TIMEZONE)
+    }
+    /// Resolves this wall-clock time in `timezone`.
+    ///
+    /// When clocks go back, the repeated hour is taken at its first
+    /// occurrence (summer time). When clocks go forward, a time inside the
+    /// skipped hour is moved forward by the length of the gap, so `02:30`
+    /// becomes `03:30`, which is what a clock that was not adjusted shows.
+    pub fn as_local_in(&self, timezone: chrono_tz::Tz) -> chrono::DateTime<chrono_tz::Tz> {
+        match self.and_local_timezone(timezone) {
+            LocalResult::Single(datetime) => datetime,
+            LocalResult::Ambiguous(earliest, _) => earliest,
+            LocalResult::None => {
+                // Offset in force right before the gap, gaps never last a day
+                let before = self.0 - chrono::Duration::days(1);
+                let offset = timezone.offset_from_local_datetime(&before).earliest();
+                let offset = offset.map_or(chrono::Duration::zero(), |offset| {
+                    chrono::Duration::seconds(offset.fix().local_minus_utc().into())
+                });
+                timezone.from_utc_datetime(&(self.0 - offset))
+            }
+        }
+    }
     pub fn as_utc(&self) -> chrono::DateTime<chrono::Utc> {
-        self.and_local_timezone(chrono_tz::CET).unwrap().to_utc()
+        self.as_local().to_utc()
+    }
+    pub fn as_utc_in(&self, timezone: chrono_tz::Tz) -> chrono::DateTime<chrono::Utc> {
+        self.as_local_in(timezone).to_utc()
+    }
+    pub fn from_utc(datetime: chrono::DateTime<chrono::Utc>) -> Self {
+        datetime.with_timezone(&TIMEZONE).naive_local().into()
     }
     pub fn today() -> Self {
-        chrono::Utc::now()
-            .with_timezone(&chrono_tz::CET)
-            .naive_local()
-            .into()
+        Self::from_utc(chrono::Utc::now())
+    }
+}
+
+/// A university year, from September 1st to August 31st.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AcademicYear {
+    pub start: CyuDate,
+    pub end: CyuDate,
+}
+
+impl AcademicYear {
+    pub const START_MONTH: u32 = 9;
+
+    /// The academic year starting in September of `year`.
+    pub fn starting_in(year: i32) -> Self {
+        let start = CyuDate::new(year, Self::START_MONTH, 1).unwrap();
+        let end = CyuDate::new(year + 1, Self::START_MONTH, 1)
+            .and_then(|date| date.pred_opt())
+            .unwrap();
+        Self {
+            start,
+            end: end.into(),
+        }
+    }
+
+    pub fn containing(date: &CyuDate) -> Self {
+        if date.month() >= Self::START_MONTH {
+            Self::starting_in(date.year())
+        } else {
+            Self::starting_in(date.year() - 1)
+        }
+    }
+
+    pub fn contains(&self, date: &CyuDate) -> bool {
+        self.start <= *date && *date <= self.end
+    }
+
+    /// Splits `start..=end` on academic year boundaries. The first and last
+    /// years are clipped to the range.
+    pub fn split(start: &CyuDate, end: &CyuDate) -> Vec<Self> {
+        let mut years = Vec::new();
+        let mut year = Self::containing(start);
+        while year.start <= *end {
+            let next = Self::starting_in(year.start.year() + 1);
+            years.push(Self {
+                start: year.start.max(start.clone()),
+                end: year.end.min(end.clone()),
+            });
+            year = next;
+        }
+        years
+    }
+}
+
+/// Half of an academic year, the first one ending with January.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct Semester {
+    pub start: CyuDate,
+    pub end: CyuDate,
+}
+
+impl Semester {
+    pub const SECOND_START_MONTH: u32 = 2;
+
+    pub fn containing(date: &CyuDate) -> Self {
+        let year = AcademicYear::containing(date);
+        let second_start = CyuDate::new(year.end.year(), Self::SECOND_START_MONTH, 1).unwrap();
+        if *date < second_start {
+            Self {
+                start: year.start,
+                end: second_start.pred_opt().unwrap().into(),
+            }
+        } else {
+            Self {
+                start: second_start,
+                end: year.end,
+            }
+        }
+    }
+
+    pub fn contains(&self, date: &CyuDate) -> bool {
+        self.start <= *date && *date <= self.end
     }
 }
 
@@ -1,3 +1,3 @@
 mod date;
 
-pub use date::{CyuDate, CyuDateTime};
+pub use date::{AcademicYear, CyuDate, CyuDateTime, Semester, TIMEZONE};
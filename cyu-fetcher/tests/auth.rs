@@ -0,0 +1,111 @@
+use chrono::NaiveDate;
+use cyu_fetcher::calendar::{CalendarView, ColorBy};
+use cyu_fetcher::utils::CyuDate;
+use cyu_fetcher::{CasLogin, Credentials, Error, Fetcher, LdapLogin, Session};
+use cyu_mock::{Event, Fixtures, MockServer};
+
+async fn server() -> MockServer {
+    let day = NaiveDate::from_ymd_opt(2024, 10, 7).unwrap();
+    MockServer::start(Fixtures {
+        events: vec![Event::new(
+            "1",
+            day.and_hms_opt(8, 0, 0).unwrap(),
+            day.and_hms_opt(10, 0, 0).unwrap(),
+            "CM",
+            &["ANALYSE"],
+        )],
+        ..Default::default()
+    })
+    .await
+}
+
+fn cas_fetcher(server: &MockServer) -> Fetcher {
+    Fetcher::builder()
+        .base_url(server.base_url())
+        .auth_strategy(CasLogin::new(server.cas_url()))
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn ldap_form() {
+    let server = server().await;
+    let fetcher = Fetcher::builder()
+        .base_url(server.base_url())
+        .auth_strategy(LdapLogin)
+        .build()
+        .unwrap();
+    let token = fetcher
+        .login("e-student".into(), "password".into())
+        .await
+        .unwrap();
+    assert_eq!(
+        fetcher.get_infos(&token).await.unwrap().federation_id,
+        "22001234"
+    );
+    assert_eq!(server.hits("/LdapLogin/Logon"), 1);
+    assert_eq!(server.raw_hits("/cas/login"), 0);
+}
+
+#[tokio::test]
+async fn cas_ticket() {
+    let server = server().await;
+    let fetcher = cas_fetcher(&server);
+    let token = fetcher
+        .login("e-student".into(), "password".into())
+        .await
+        .unwrap();
+    // The ticket-granting cookie stays with the CAS server
+    assert!(token.get("TGC").is_none());
+    let infos = fetcher.get_infos(&token).await.unwrap();
+    assert_eq!(infos.federation_id, "22001234");
+    assert_eq!(infos.display_name, "STUDENT Jane");
+    assert_eq!(server.hits("/CasLogin"), 1);
+    assert_eq!(server.hits("/LdapLogin"), 0);
+}
+
+#[tokio::test]
+async fn cas_bad_credentials() {
+    let server = server().await;
+    let fetcher = cas_fetcher(&server);
+    let result = fetcher.login("e-student".into(), "wrong".into()).await;
+    assert!(matches!(result, Err(Error::Unauthorized)));
+    assert_eq!(server.hits("/CasLogin"), 0);
+}
+
+#[tokio::test]
+async fn cas_not_found() {
+    let server = server().await;
+    let fetcher = Fetcher::builder()
+        .base_url(server.base_url())
+        .auth_strategy(CasLogin::new(format!("http://{}/sso", server.addr())))
+        .build()
+        .unwrap();
+    let result = fetcher.login("e-student".into(), "password".into()).await;
+    assert!(matches!(result, Err(Error::Status { .. })), "{result:?}");
+}
+
+#[tokio::test]
+async fn session_renewed_through_cas() {
+    let server = server().await;
+    let session = Session::new(
+        cas_fetcher(&server),
+        Credentials {
+            username: "e-student".into(),
+            password: "password".into(),
+        },
+    );
+    let day = CyuDate::new(2024, 10, 7).unwrap();
+    let get_day = || {
+        session.get_calendar(
+            day.clone(),
+            day.clone(),
+            CalendarView::Day,
+            ColorBy::EventCategory,
+        )
+    };
+    assert_eq!(get_day().await.unwrap().len(), 1);
+    server.expire_sessions();
+    assert_eq!(get_day().await.unwrap().len(), 1);
+    assert_eq!(server.hits("/CasLogin"), 2);
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use chrono::{NaiveTime, TimeDelta};
+use cyu_fetcher::calendar::{
+    find_free_slots, AvailabilityOptions, FreeSlot, GetCalendarResponseElement,
+};
+use cyu_fetcher::utils::{CyuDate, CyuDateTime};
+
+fn event(start: &str, end: Option<&str>, all_day: bool) -> GetCalendarResponseElement {
+    common::event()
+        .id(start)
+        .start(start)
+        .end(end)
+        .all_day(all_day)
+        .description("CM")
+        .sites(&[])
+        .build()
+}
+
+fn slot(day: u32, from: (u32, u32), to: (u32, u32)) -> FreeSlot {
+    FreeSlot {
+        start: CyuDateTime::new(2024, 10, day, from.0, from.1, 0).unwrap(),
+        end: CyuDateTime::new(2024, 10, day, to.0, to.1, 0).unwrap(),
+    }
+}
+
+fn day(day: u32) -> CyuDate {
+    CyuDate::new(2024, 10, day).unwrap()
+}
+
+#[test]
+fn free_day() {
+    let slots = find_free_slots([], &day(7), &day(7), &AvailabilityOptions::default());
+    assert_eq!(slots, vec![slot(7, (8, 0), (19, 0))]);
+    assert_eq!(slots[0].duration(), TimeDelta::hours(11));
+}
+
+#[test]
+fn common_availability_of_two_groups() {
+    let first = [
+        event("2024-10-07T08:30:00", Some("2024-10-07T10:00:00"), false),
+        event("2024-10-07T13:00:00", Some("2024-10-07T15:00:00"), false),
+    ];
+    let second = [
+        event("2024-10-07T09:30:00", Some("2024-10-07T11:30:00"), false),
+        event("2024-10-07T16:00:00", Some("2024-10-07T18:30:00"), false),
+    ];
+    let slots = find_free_slots(
+        first.iter().chain(&second),
+        &day(7),
+        &day(7),
+        &AvailabilityOptions::default(),
+    );
+    assert_eq!(
+        slots,
+        vec![slot(7, (11, 30), (13, 0)), slot(7, (15, 0), (16, 0))]
+    );
+}
+
+#[test]
+fn short_windows_are_left_out() {
+    let events = [
+        event("2024-10-07T08:45:00", Some("2024-10-07T12:00:00"), false),
+        event("2024-10-07T12:30:00", Some("2024-10-07T18:00:00"), false),
+    ];
+    let options = AvailabilityOptions {
+        min_duration: TimeDelta::minutes(45),
+        ..Default::default()
+    };
+    let slots = find_free_slots(&events, &day(7), &day(7), &options);
+    assert_eq!(
+        slots,
+        vec![slot(7, (8, 0), (8, 45)), slot(7, (18, 0), (19, 0))]
+    );
+}
+
+#[test]
+fn weekends_and_working_hours() {
+    let options = AvailabilityOptions {
+        day_start: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+        day_end: NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
+        ..Default::default()
+    };
+    // Friday to Monday
+    let slots = find_free_slots([], &day(11), &day(14), &options);
+    assert_eq!(
+        slots,
+        vec![slot(11, (9, 0), (12, 0)), slot(14, (9, 0), (12, 0))]
+    );
+
+    let weekends = AvailabilityOptions {
+        skip_weekends: false,
+        ..options
+    };
+    assert_eq!(find_free_slots([], &day(11), &day(14), &weekends).len(), 4);
+}
+
+#[test]
+fn all_day_and_open_ended_events() {
+    let events = [
+        event("2024-10-07T00:00:00", None, true),
+        event("2024-10-08T10:00:00", None, false),
+    ];
+    let slots = find_free_slots(&events, &day(7), &day(8), &AvailabilityOptions::default());
+    assert_eq!(
+        slots,
+        vec![slot(8, (8, 0), (10, 0)), slot(8, (11, 0), (19, 0))]
+    );
+}
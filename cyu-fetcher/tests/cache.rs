@@ -0,0 +1,207 @@
+use chrono::NaiveDate;
+use cyu_fetcher::calendar::{
+    CalendarView, ColorBy, GetAllQuery, GetCalendarQuery, GetLimitsQuery, ResourceType,
+};
+use cyu_fetcher::utils::CyuDate;
+use cyu_fetcher::{CacheConfig, Error, Fetcher, SessionCookies};
+use cyu_mock::{Event, Fixtures, MockServer};
+use std::time::Duration;
+
+const CALENDAR_DATA: &str = "/Home/GetCalendarData";
+
+fn datetime(day: u32, hour: u32) -> chrono::NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 10, day)
+        .unwrap()
+        .and_hms_opt(hour, 0, 0)
+        .unwrap()
+}
+
+async fn setup(config: CacheConfig) -> (MockServer, Fetcher, SessionCookies) {
+    let server = MockServer::start(Fixtures {
+        events: vec![Event::new(
+            "1",
+            datetime(7, 8),
+            datetime(7, 10),
+            "CM",
+            &["ANALYSE"],
+        )],
+        ..Default::default()
+    })
+    .await;
+    let fetcher = Fetcher::builder()
+        .base_url(server.base_url())
+        .cache(config)
+        .build()
+        .unwrap();
+    let token = fetcher
+        .login("e-student".into(), "password".into())
+        .await
+        .unwrap();
+    (server, fetcher, token)
+}
+
+fn week(token: &SessionCookies) -> GetCalendarQuery {
+    GetCalendarQuery {
+        id: "22001234".into(),
+        resource_type: ResourceType::Student,
+        token: token.clone(),
+        start: CyuDate::new(2024, 10, 7).unwrap(),
+        end: CyuDate::new(2024, 10, 13).unwrap(),
+        view: CalendarView::Week,
+        color_by: ColorBy::EventCategory,
+    }
+}
+
+fn add_event(server: &MockServer) {
+    server.update(|fixtures| {
+        fixtures.events.push(Event::new(
+            "2",
+            datetime(8, 13),
+            datetime(8, 15),
+            "TD",
+            &["PROBABILITES"],
+        ))
+    });
+}
+
+#[tokio::test]
+async fn fresh_entries_are_served_from_memory() {
+    let (server, fetcher, token) = setup(CacheConfig::default()).await;
+    let first = fetcher.get_calendar(week(&token)).await.unwrap();
+    add_event(&server);
+    let second = fetcher.get_calendar(week(&token)).await.unwrap();
+    assert_eq!(first.len(), 1);
+    assert_eq!(second.len(), 1);
+    assert_eq!(server.hits(CALENDAR_DATA), 1);
+
+    let mut other_range = week(&token);
+    other_range.end = CyuDate::new(2024, 10, 12).unwrap();
+    assert_eq!(fetcher.get_calendar(other_range).await.unwrap().len(), 2);
+    assert_eq!(server.hits(CALENDAR_DATA), 2);
+}
+
+#[tokio::test]
+async fn entries_are_bound_to_the_session() {
+    let (server, fetcher, token) = setup(CacheConfig::default()).await;
+    fetcher.get_calendar(week(&token)).await.unwrap();
+    let forged = [(".AspNetCore.Cookies", "forged")].into_iter().collect();
+    let result = fetcher.get_calendar(week(&forged)).await;
+    assert!(matches!(result, Err(Error::Unauthorized)));
+    assert_eq!(server.hits(CALENDAR_DATA), 2);
+}
+
+#[tokio::test]
+async fn entries_are_shared_by_the_sessions_of_a_user() {
+    let (server, fetcher, token) = setup(CacheConfig::default()).await;
+    fetcher.get_calendar(week(&token)).await.unwrap();
+    let other_login = fetcher
+        .login("e-student".into(), "password".into())
+        .await
+        .unwrap();
+    assert_ne!(other_login, token);
+    let calendar = fetcher.get_calendar(week(&other_login)).await.unwrap();
+    assert_eq!(calendar.len(), 1);
+    assert_eq!(server.hits(CALENDAR_DATA), 1);
+}
+
+#[tokio::test]
+async fn academic_years_are_keyed_by_date() {
+    let (server, fetcher, token) = setup(CacheConfig::default()).await;
+    let query = |day| GetLimitsQuery {
+        id: "22001234",
+        resource_type: ResourceType::Student,
+        token: &token,
+        date: Some(CyuDate::new(2024, 10, day).unwrap()),
+    };
+    fetcher.get_academic_years(query(7)).await.unwrap();
+    let home_hits = server.hits("/");
+    fetcher.get_academic_years(query(7)).await.unwrap();
+    assert_eq!(server.hits("/"), home_hits);
+    fetcher.get_academic_years(query(8)).await.unwrap();
+    assert_eq!(server.hits("/"), home_hits + 1);
+}
+
+#[tokio::test]
+async fn invalidation_forces_a_new_request() {
+    let (server, fetcher, token) = setup(CacheConfig::default()).await;
+    fetcher.get_calendar(week(&token)).await.unwrap();
+    add_event(&server);
+    fetcher.invalidate_cache("22001234");
+    let calendar = fetcher.get_calendar(week(&token)).await.unwrap();
+    assert_eq!(calendar.len(), 2);
+    assert_eq!(server.hits(CALENDAR_DATA), 2);
+}
+
+#[tokio::test]
+async fn invalidation_forgets_every_resource_of_the_user() {
+    let (server, fetcher, token) = setup(CacheConfig::default()).await;
+    let room = || GetCalendarQuery {
+        id: "PC-CH-329".into(),
+        resource_type: ResourceType::Room,
+        ..week(&token)
+    };
+    fetcher.get_calendar(room()).await.unwrap();
+    let name_hits = server.hits("/Home/LoadDisplayNames");
+    fetcher.invalidate_cache("22001234");
+    fetcher.get_calendar(room()).await.unwrap();
+    assert_eq!(server.hits(CALENDAR_DATA), 2);
+    // The session is verified again
+    assert_eq!(server.hits("/Home/LoadDisplayNames"), name_hits + 1);
+}
+
+#[tokio::test]
+async fn stale_entries_are_revalidated_in_the_background() {
+    let (server, fetcher, token) = setup(CacheConfig {
+        ttl: Duration::ZERO,
+        stale_while_revalidate: Duration::from_secs(60),
+    })
+    .await;
+    fetcher.get_calendar(week(&token)).await.unwrap();
+    add_event(&server);
+
+    let stale = fetcher.get_calendar(week(&token)).await.unwrap();
+    assert_eq!(stale.len(), 1);
+    for _ in 0..50 {
+        if server.hits(CALENDAR_DATA) == 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(server.hits(CALENDAR_DATA), 2);
+
+    // The refreshed copy is stale too, and served while refreshed again
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let refreshed = fetcher.get_calendar(week(&token)).await.unwrap();
+    assert_eq!(refreshed.len(), 2);
+}
+
+#[tokio::test]
+async fn expired_entries_are_fetched_again() {
+    let (server, fetcher, token) = setup(CacheConfig {
+        ttl: Duration::ZERO,
+        stale_while_revalidate: Duration::ZERO,
+    })
+    .await;
+    fetcher.get_calendar(week(&token)).await.unwrap();
+    add_event(&server);
+    let calendar = fetcher.get_calendar(week(&token)).await.unwrap();
+    assert_eq!(calendar.len(), 2);
+    assert_eq!(server.hits(CALENDAR_DATA), 2);
+}
+
+#[tokio::test]
+async fn whole_year_is_cached() {
+    let (server, fetcher, token) = setup(CacheConfig::default()).await;
+    let query = || GetAllQuery {
+        id: "22001234".into(),
+        token: token.clone(),
+        color_by: ColorBy::EventCategory,
+    };
+    fetcher.get_all_calendar(query()).await.unwrap();
+    let (home_hits, data_hits) = (server.hits("/"), server.hits(CALENDAR_DATA));
+    let calendar = fetcher.get_all_calendar(query()).await.unwrap();
+    assert!(calendar.is_complete());
+    assert_eq!(calendar.events.len(), 1);
+    assert_eq!(server.hits("/"), home_hits);
+    assert_eq!(server.hits(CALENDAR_DATA), data_hits);
+}
@@ -0,0 +1,57 @@
+use cyu_fetcher::calendar::{EventCategory, EventKind};
+
+#[test]
+fn parses_known_codes() {
+    assert_eq!(EventKind::from("CM"), EventKind::Lecture);
+    assert_eq!(EventKind::from(" td "), EventKind::Tutorial);
+    assert_eq!(EventKind::from("Examens"), EventKind::Exam);
+    assert_eq!(EventKind::from("Réunion"), EventKind::Meeting);
+    assert_eq!(EventKind::from("Férié"), EventKind::Holiday);
+}
+
+#[test]
+fn keeps_unknown_codes() {
+    let category = EventCategory::from("Conférence");
+    assert_eq!(category.kind, EventKind::Other("Conférence".into()));
+    assert_eq!(category.to_string(), "Conférence");
+    assert!(!category.is_exam());
+    assert_eq!(category.default_color(), "#77767b");
+}
+
+#[test]
+fn exams() {
+    assert!(EventKind::Exam.is_exam());
+    assert!(EventCategory::from("Contrôle continu").is_exam());
+    assert!(EventCategory::from("Partiel").is_exam());
+    assert!(!EventKind::Lecture.is_exam());
+}
+
+#[test]
+fn default_colors() {
+    assert_eq!(EventKind::Lecture.default_color(), "#3584e4");
+    assert_eq!(EventCategory::from("TD").default_color(), "#33d17a");
+    assert_eq!(
+        EventKind::Exam.default_color(),
+        EventKind::ContinuousAssessment.default_color()
+    );
+}
+
+#[test]
+fn serde_round_trip() {
+    let category: EventCategory = serde_json::from_str(r#""TP""#).unwrap();
+    assert_eq!(category.kind, EventKind::Practical);
+    assert_eq!(serde_json::to_string(&category).unwrap(), r#""TP""#);
+}
+
+#[test]
+fn serializes_as_given() {
+    for raw in ["Cours", "Partiel", "Férié"] {
+        let category = EventCategory::from(raw);
+        assert_eq!(serde_json::to_value(&category).unwrap(), raw);
+    }
+    assert_eq!(EventCategory::from("Cours").kind, EventKind::Lecture);
+    assert_eq!(
+        EventCategory::from("Cours").display_name(),
+        "Cours magistral"
+    );
+}
@@ -0,0 +1,85 @@
+//! Helpers shared by the integration tests.
+#![allow(dead_code)]
+
+use cyu_fetcher::calendar::GetCalendarResponseElement;
+use serde_json::{json, Value};
+
+/// An event as the calendar endpoint returns it.
+pub struct EventBuilder(Value);
+
+/// The Monday morning lecture of Architecture logicielle, at CHENES.
+pub fn event() -> EventBuilder {
+    EventBuilder(json!({
+        "id": "1",
+        "start": "2024-10-07T08:30:00",
+        "end": "2024-10-07T10:00:00",
+        "allDay": false,
+        "description": "CM\r\n\r\nPC-CH-329 [CHENES 1 - 329]<br />\r\n\r\nL3 INFORMATIQUE<br />\r\n\r\nArchitecture logicielle CM<br />\r\n\r\nDUPONT Jean<br />\r\n\r\nCHENES\r\n",
+        "backgroundColor": "#7D4F72",
+        "department": "CY Tech",
+        "faculty": null,
+        "eventCategory": "CM",
+        "sites": ["CHENES"],
+        "modules": null,
+    }))
+}
+
+/// `null` for no values, as Celcat does.
+fn list(values: &[&str]) -> Value {
+    if values.is_empty() {
+        Value::Null
+    } else {
+        json!(values)
+    }
+}
+
+impl EventBuilder {
+    fn set(mut self, key: &str, value: Value) -> Self {
+        self.0[key] = value;
+        self
+    }
+
+    pub fn id(self, id: &str) -> Self {
+        self.set("id", json!(id))
+    }
+
+    pub fn start(self, start: &str) -> Self {
+        self.set("start", json!(start))
+    }
+
+    pub fn end<'a>(self, end: impl Into<Option<&'a str>>) -> Self {
+        self.set("end", json!(end.into()))
+    }
+
+    pub fn all_day(self, all_day: bool) -> Self {
+        self.set("allDay", json!(all_day))
+    }
+
+    pub fn description(self, description: &str) -> Self {
+        self.set("description", json!(description))
+    }
+
+    pub fn category(self, category: &str) -> Self {
+        self.set("eventCategory", json!(category))
+    }
+
+    pub fn sites(self, sites: &[&str]) -> Self {
+        self.set("sites", list(sites))
+    }
+
+    pub fn modules(self, modules: &[&str]) -> Self {
+        self.set("modules", list(modules))
+    }
+
+    /// Overrides the raw fields of `fields`.
+    pub fn fields(mut self, fields: Value) -> Self {
+        for (key, value) in fields.as_object().unwrap() {
+            self.0[key] = value.clone();
+        }
+        self
+    }
+
+    pub fn build(self) -> GetCalendarResponseElement {
+        serde_json::from_value(self.0).unwrap()
+    }
+}
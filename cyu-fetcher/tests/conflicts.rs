@@ -0,0 +1,153 @@
+mod common;
+
+use chrono::TimeDelta;
+use cyu_fetcher::calendar::{
+    find_conflicts, ConflictKind, ConflictOptions, GetCalendarResponseElement,
+};
+use cyu_fetcher::sites::SiteRegistry;
+use serde_json::json;
+
+fn event(id: &str, start: &str, end: &str, site: &str) -> GetCalendarResponseElement {
+    common::event()
+        .id(id)
+        .start(start)
+        .end(end)
+        .description("CM")
+        .sites(&[site])
+        .build()
+}
+
+#[test]
+fn no_conflicts() {
+    let events = [
+        event("1", "2024-10-07T08:30:00", "2024-10-07T10:00:00", "CHENES"),
+        event("2", "2024-10-07T10:00:00", "2024-10-07T12:00:00", "CHENES"),
+        event(
+            "3",
+            "2024-10-07T13:00:00",
+            "2024-10-07T15:00:00",
+            "SAINT MARTIN",
+        ),
+    ];
+    assert!(find_conflicts(
+        &events,
+        SiteRegistry::embedded(),
+        &ConflictOptions::default()
+    )
+    .is_empty());
+}
+
+#[test]
+fn overlapping_sessions() {
+    let events = [
+        event("2", "2024-10-07T09:00:00", "2024-10-07T11:00:00", "CHENES"),
+        event("1", "2024-10-07T08:30:00", "2024-10-07T10:00:00", "CHENES"),
+        event("3", "2024-10-07T09:30:00", "2024-10-07T09:45:00", "CHENES"),
+    ];
+    let report = find_conflicts(
+        &events,
+        SiteRegistry::embedded(),
+        &ConflictOptions::default(),
+    );
+    let pairs = report
+        .conflicts
+        .iter()
+        .map(|conflict| (conflict.first.id().as_str(), conflict.second.id().as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(pairs, vec![("1", "2"), ("1", "3"), ("2", "3")]);
+    assert_eq!(
+        report.conflicts[0].kind,
+        ConflictKind::Overlap {
+            duration: TimeDelta::hours(1)
+        }
+    );
+    assert_eq!(
+        report.conflicts[1].kind,
+        ConflictKind::Overlap {
+            duration: TimeDelta::minutes(15)
+        }
+    );
+    assert_eq!(report.involving("3").count(), 2);
+}
+
+#[test]
+fn not_enough_time_to_change_site() {
+    let events = [
+        event("1", "2024-10-07T08:30:00", "2024-10-07T10:00:00", "CHENES"),
+        event(
+            "2",
+            "2024-10-07T10:00:00",
+            "2024-10-07T12:00:00",
+            "SAINT MARTIN",
+        ),
+        event("3", "2024-10-07T13:00:00", "2024-10-07T15:00:00", "PORT"),
+    ];
+    let report = find_conflicts(
+        &events,
+        SiteRegistry::embedded(),
+        &ConflictOptions::default(),
+    );
+    assert_eq!(report.conflicts.len(), 1);
+    let ConflictKind::TravelTime {
+        from,
+        to,
+        needed,
+        available,
+    } = &report.conflicts[0].kind
+    else {
+        panic!("unexpected conflict {:?}", report.conflicts[0]);
+    };
+    assert_eq!((from.as_str(), to.as_str()), ("CHENES", "SAINT MARTIN"));
+    assert_eq!(*available, TimeDelta::zero());
+    assert!(*needed > TimeDelta::minutes(10) && *needed < TimeDelta::minutes(30));
+
+    let faster = ConflictOptions {
+        walking_speed: 1000.0,
+        changeover: TimeDelta::zero(),
+    };
+    let report = find_conflicts(&events, SiteRegistry::embedded(), &faster);
+    assert_eq!(report.conflicts.len(), 1);
+    let later = [
+        events[0].clone(),
+        event(
+            "2",
+            "2024-10-07T10:30:00",
+            "2024-10-07T12:00:00",
+            "SAINT MARTIN",
+        ),
+    ];
+    assert!(find_conflicts(
+        &later,
+        SiteRegistry::embedded(),
+        &ConflictOptions::default()
+    )
+    .is_empty());
+}
+
+#[test]
+fn unknown_sites_and_all_day_events_are_ignored() {
+    let mut holiday = serde_json::to_value(event(
+        "1",
+        "2024-10-07T00:00:00",
+        "2024-10-08T00:00:00",
+        "CHENES",
+    ))
+    .unwrap();
+    holiday["allDay"] = json!(true);
+    let events = [
+        serde_json::from_value(holiday).unwrap(),
+        event("2", "2024-10-07T08:30:00", "2024-10-07T10:00:00", "CHENES"),
+        event(
+            "3",
+            "2024-10-07T10:00:00",
+            "2024-10-07T12:00:00",
+            "NEUVILLE",
+        ),
+    ];
+    assert!(find_conflicts(
+        &events,
+        SiteRegistry::embedded(),
+        &ConflictOptions::default()
+    )
+    .is_empty());
+}
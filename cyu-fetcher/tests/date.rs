@@ -0,0 +1,63 @@
+use chrono::{DateTime, Utc};
+use cyu_fetcher::utils::{AcademicYear, CyuDate, CyuDateTime};
+
+fn utc(s: &str) -> DateTime<Utc> {
+    s.parse().unwrap()
+}
+
+#[test]
+fn winter_and_summer_offsets() {
+    let winter = CyuDateTime::new(2025, 1, 15, 8, 30, 0).unwrap();
+    assert_eq!(winter.as_utc(), utc("2025-01-15T07:30:00Z"));
+    let summer = CyuDateTime::new(2025, 6, 15, 8, 30, 0).unwrap();
+    assert_eq!(summer.as_utc(), utc("2025-06-15T06:30:00Z"));
+}
+
+#[test]
+fn spring_forward_gap() {
+    // 2025-03-30 02:00 CET jumps to 03:00 CEST
+    let before = CyuDateTime::new(2025, 3, 30, 1, 59, 0).unwrap();
+    assert_eq!(before.as_utc(), utc("2025-03-30T00:59:00Z"));
+    let gap = CyuDateTime::new(2025, 3, 30, 2, 30, 0).unwrap();
+    assert_eq!(gap.as_utc(), utc("2025-03-30T01:30:00Z"));
+    assert_eq!(gap.as_local().format("%H:%M").to_string(), "03:30");
+    let after = CyuDateTime::new(2025, 3, 30, 3, 0, 0).unwrap();
+    assert_eq!(after.as_utc(), utc("2025-03-30T01:00:00Z"));
+}
+
+#[test]
+fn fall_back_ambiguous_hour() {
+    // 2025-10-26 03:00 CEST goes back to 02:00 CET
+    let ambiguous = CyuDateTime::new(2025, 10, 26, 2, 30, 0).unwrap();
+    assert_eq!(ambiguous.as_utc(), utc("2025-10-26T00:30:00Z"));
+    let after = CyuDateTime::new(2025, 10, 26, 3, 0, 0).unwrap();
+    assert_eq!(after.as_utc(), utc("2025-10-26T02:00:00Z"));
+}
+
+#[test]
+fn from_utc_round_trip() {
+    for instant in [
+        "2025-03-30T00:30:00Z",
+        "2025-03-30T01:30:00Z",
+        "2025-10-26T00:30:00Z",
+        "2025-10-26T02:30:00Z",
+    ] {
+        let local = CyuDateTime::from_utc(utc(instant));
+        assert_eq!(local.as_utc(), utc(instant), "{instant}");
+    }
+    // The second occurrence of the repeated hour cannot be told apart
+    let second_occurrence = CyuDateTime::from_utc(utc("2025-10-26T01:30:00Z"));
+    assert_eq!(
+        second_occurrence,
+        CyuDateTime::new(2025, 10, 26, 2, 30, 0).unwrap()
+    );
+}
+
+#[test]
+fn academic_year_boundaries() {
+    let year = AcademicYear::containing(&CyuDate::new(2025, 8, 31).unwrap());
+    assert_eq!(year, AcademicYear::starting_in(2024));
+    assert_eq!(year.end, CyuDate::new(2025, 8, 31).unwrap());
+    let year = AcademicYear::containing(&CyuDate::new(2025, 9, 1).unwrap());
+    assert_eq!(year.start, CyuDate::new(2025, 9, 1).unwrap());
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use cyu_fetcher::calendar::{EventCategory, EventDetails};
+
+fn event(category: &str, description: &str, sites: &[&str], modules: &[&str]) -> EventDetails {
+    common::event()
+        .category(category)
+        .description(description)
+        .sites(sites)
+        .modules(modules)
+        .build()
+        .details()
+}
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn lecture_with_site_and_module_suffix() {
+    let details = event(
+        "CM",
+        "CM\r\n\r\nPC-CH-329 [CHENES 1 - 329]<br />\r\n\r\nL3 INFORMATIQUE<br />\r\n\r\nArchitecture logicielle CM<br />\r\n\r\nDUPONT Jean<br />\r\n\r\nCHENES\r\n",
+        &["CHENES"],
+        &[],
+    );
+    assert_eq!(
+        details,
+        EventDetails {
+            category: Some(EventCategory::from("CM")),
+            module: Some("Architecture logicielle".into()),
+            rooms: strings(&["PC-CH-329 [CHENES 1 - 329]"]),
+            teachers: strings(&["DUPONT Jean"]),
+            groups: strings(&["L3 INFORMATIQUE"]),
+            remarks: vec![],
+        }
+    );
+}
+
+#[test]
+fn tutorial_with_several_groups_and_teachers() {
+    let details = event(
+        "TD",
+        "TD<br />\r\n\r\nE213<br />\r\n\r\nE214<br />\r\n\r\nL2 MATHS GR1<br />\r\n\r\nL2 MATHS GR2<br />\r\n\r\nProbabilités [TD]<br />\r\n\r\nMARTIN Paul<br />\r\n\r\nLE GALL Marie-Anne<br />\r\n\r\nSAINT MARTIN",
+        &["SAINT MARTIN"],
+        &[],
+    );
+    assert_eq!(details.module.as_deref(), Some("Probabilités"));
+    assert_eq!(details.rooms, strings(&["E213", "E214"]));
+    assert_eq!(details.groups, strings(&["L2 MATHS GR1", "L2 MATHS GR2"]));
+    assert_eq!(
+        details.teachers,
+        strings(&["MARTIN Paul", "LE GALL Marie-Anne"])
+    );
+    assert!(details.remarks.is_empty());
+}
+
+#[test]
+fn exam_with_remarks() {
+    let details = event(
+        "Examen",
+        "Examen<br />\r\n\r\nAmphi Turing<br />\r\n\r\nM1 MIAGE FA<br />\r\n\r\nBases de données<br />\r\n\r\nSession 1 - calculatrice interdite",
+        &[],
+        &[],
+    );
+    assert_eq!(details.module.as_deref(), Some("Bases de données"));
+    assert_eq!(details.rooms, strings(&["Amphi Turing"]));
+    assert_eq!(details.groups, strings(&["M1 MIAGE FA"]));
+    assert!(details.teachers.is_empty());
+    assert_eq!(
+        details.remarks,
+        strings(&["Session 1 - calculatrice interdite"])
+    );
+}
+
+#[test]
+fn module_hint_wins_over_position() {
+    let details = event(
+        "TP",
+        "TP<br />\r\n\r\nRéservé aux redoublants<br />\r\n\r\nPO-B-104<br />\r\n\r\nProgrammation C TP<br />\r\n\r\nDURAND Éloïse",
+        &["PORT"],
+        &["Programmation C"],
+    );
+    assert_eq!(details.module.as_deref(), Some("Programmation C"));
+    assert_eq!(details.rooms, strings(&["PO-B-104"]));
+    assert_eq!(details.teachers, strings(&["DURAND Éloïse"]));
+    assert_eq!(details.remarks, strings(&["Réservé aux redoublants"]));
+}
+
+#[test]
+fn holiday_without_category_line() {
+    let details = event("Vacances", "Vacances de la Toussaint", &[], &[]);
+    assert_eq!(details.category, Some(EventCategory::from("Vacances")));
+    assert_eq!(details.module.as_deref(), Some("Vacances de la Toussaint"));
+    assert!(details.rooms.is_empty());
+}
+
+#[test]
+fn category_only() {
+    let details = event("Réunion", "Réunion<br />\r\n", &[], &[]);
+    assert_eq!(details.category, Some(EventCategory::from("Réunion")));
+    assert_eq!(details.module, None);
+    assert!(details.remarks.is_empty());
+}
+
+#[test]
+fn html_entities_and_remote_session() {
+    let details = event(
+        "CM",
+        "CM<br />\r\n\r\nDistanciel<br />\r\n\r\nDroit &amp; &#233;conomie<br />\r\n\r\nBERNARD Luc",
+        &[],
+        &[],
+    );
+    assert_eq!(details.module.as_deref(), Some("Droit & économie"));
+    assert_eq!(details.rooms, strings(&["Distanciel"]));
+    assert_eq!(details.teachers, strings(&["BERNARD Luc"]));
+}
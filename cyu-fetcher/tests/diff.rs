@@ -0,0 +1,238 @@
+mod common;
+
+use chrono::TimeDelta;
+use cyu_fetcher::calendar::{
+    diff_calendars, DiffOptions, EventCategory, EventChange, GetCalendarResponseElement, MatchedBy,
+};
+use cyu_fetcher::utils::CyuDateTime;
+
+fn event(
+    id: &str,
+    start: &str,
+    end: &str,
+    category: &str,
+    room: &str,
+) -> GetCalendarResponseElement {
+    common::event()
+        .id(id)
+        .start(start)
+        .end(end)
+        .description(&format!("{category}\r\n\r\n{room}<br />\r\n\r\nAnalyse<br />\r\n\r\nDUPONT Jean<br />\r\n\r\nCHENES\r\n"))
+        .category(category)
+        .modules(&["Analyse"])
+        .build()
+}
+
+fn ids(events: &[GetCalendarResponseElement]) -> Vec<&str> {
+    events.iter().map(|event| event.id().as_str()).collect()
+}
+
+#[test]
+fn identical_snapshots() {
+    let snapshot = vec![event(
+        "1",
+        "2024-10-07T08:30:00",
+        "2024-10-07T10:00:00",
+        "CM",
+        "E213",
+    )];
+    let diff = diff_calendars(&snapshot, &snapshot, DiffOptions::default());
+    assert!(diff.is_empty());
+}
+
+#[test]
+fn added_and_removed_events() {
+    let before = vec![event(
+        "1",
+        "2024-10-07T08:30:00",
+        "2024-10-07T10:00:00",
+        "CM",
+        "E213",
+    )];
+    let after = vec![event(
+        "2",
+        "2024-10-28T08:30:00",
+        "2024-10-28T10:00:00",
+        "CM",
+        "E213",
+    )];
+    let diff = diff_calendars(&before, &after, DiffOptions::default());
+    assert_eq!(ids(&diff.added), vec!["2"]);
+    assert_eq!(ids(&diff.removed), vec!["1"]);
+    assert!(diff.modified.is_empty());
+}
+
+#[test]
+fn room_and_time_changes() {
+    let before = vec![
+        event(
+            "1",
+            "2024-10-07T08:30:00",
+            "2024-10-07T10:00:00",
+            "CM",
+            "E213",
+        ),
+        event(
+            "2",
+            "2024-10-08T13:00:00",
+            "2024-10-08T15:00:00",
+            "TD",
+            "A101",
+        ),
+    ];
+    let after = vec![
+        event(
+            "1",
+            "2024-10-07T08:30:00",
+            "2024-10-07T10:00:00",
+            "CM",
+            "E214",
+        ),
+        event(
+            "2",
+            "2024-10-08T15:00:00",
+            "2024-10-08T17:00:00",
+            "TD",
+            "A101",
+        ),
+    ];
+    let diff = diff_calendars(&before, &after, DiffOptions::default());
+    assert!(diff.added.is_empty() && diff.removed.is_empty());
+    assert_eq!(diff.moved().count(), 2);
+
+    assert_eq!(diff.modified[0].matched_by, MatchedBy::Id);
+    assert_eq!(
+        diff.modified[0].changes,
+        vec![EventChange::RoomChanged {
+            before: vec!["E213".into()],
+            after: vec!["E214".into()],
+        }]
+    );
+    let [EventChange::Rescheduled { before, after }] = &diff.modified[1].changes[..] else {
+        panic!("unexpected changes {:?}", diff.modified[1].changes);
+    };
+    assert_eq!(
+        before.start,
+        CyuDateTime::new(2024, 10, 8, 13, 0, 0).unwrap()
+    );
+    assert_eq!(
+        after.start,
+        CyuDateTime::new(2024, 10, 8, 15, 0, 0).unwrap()
+    );
+}
+
+#[test]
+fn category_and_description_changes() {
+    let before = vec![event(
+        "1",
+        "2024-10-07T08:30:00",
+        "2024-10-07T10:00:00",
+        "CM",
+        "E213",
+    )];
+    let after = vec![event(
+        "1",
+        "2024-10-07T08:30:00",
+        "2024-10-07T10:00:00",
+        "Examen",
+        "E213",
+    )];
+    let diff = diff_calendars(&before, &after, DiffOptions::default());
+    let changes = &diff.modified[0].changes;
+    assert_eq!(
+        changes[0],
+        EventChange::CategoryChanged {
+            before: EventCategory::from("CM"),
+            after: EventCategory::from("Examen"),
+        }
+    );
+    assert!(matches!(changes[1], EventChange::DescriptionChanged { .. }));
+    assert_eq!(changes.len(), 2);
+}
+
+#[test]
+fn recreated_events_are_matched_by_similarity() {
+    let before = vec![
+        event(
+            "1",
+            "2024-10-07T08:30:00",
+            "2024-10-07T10:00:00",
+            "CM",
+            "E213",
+        ),
+        event(
+            "2",
+            "2024-10-09T08:30:00",
+            "2024-10-09T10:00:00",
+            "TD",
+            "E213",
+        ),
+    ];
+    let after = vec![
+        event(
+            "10",
+            "2024-10-08T08:30:00",
+            "2024-10-08T10:00:00",
+            "CM",
+            "E213",
+        ),
+        event(
+            "11",
+            "2024-10-09T08:30:00",
+            "2024-10-09T10:00:00",
+            "TD",
+            "E213",
+        ),
+    ];
+    let diff = diff_calendars(&before, &after, DiffOptions::default());
+    assert!(diff.added.is_empty() && diff.removed.is_empty());
+    // The tutorial only changed id
+    assert_eq!(diff.modified.len(), 1);
+    assert_eq!(diff.modified[0].before.id(), "1");
+    assert_eq!(diff.modified[0].after.id(), "10");
+    assert_eq!(diff.modified[0].matched_by, MatchedBy::Similarity);
+    assert!(matches!(
+        diff.modified[0].changes[..],
+        [EventChange::Rescheduled { .. }]
+    ));
+}
+
+#[test]
+fn fuzzy_matching_pairs_closest_events() {
+    let before = vec![
+        event(
+            "1",
+            "2024-10-07T08:30:00",
+            "2024-10-07T10:00:00",
+            "CM",
+            "E213",
+        ),
+        event(
+            "2",
+            "2024-10-10T08:30:00",
+            "2024-10-10T10:00:00",
+            "CM",
+            "E213",
+        ),
+    ];
+    let after = vec![event(
+        "3",
+        "2024-10-09T08:30:00",
+        "2024-10-09T10:00:00",
+        "CM",
+        "E213",
+    )];
+    let diff = diff_calendars(&before, &after, DiffOptions::default());
+    assert_eq!(diff.modified[0].before.id(), "2");
+    assert_eq!(ids(&diff.removed), vec!["1"]);
+
+    let strict = DiffOptions { fuzzy_window: None };
+    let diff = diff_calendars(&before, &after, strict);
+    assert_eq!(ids(&diff.added), vec!["3"]);
+    assert_eq!(diff.removed.len(), 2);
+
+    let narrow = DiffOptions {
+        fuzzy_window: Some(TimeDelta::hours(12)),
+    };
+    assert!(diff_calendars(&before, &after, narrow).modified.is_empty());
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use chrono::{DateTime, TimeDelta, TimeZone as _, Utc};
+use cyu_fetcher::calendar::GetCalendarResponseElement;
+use cyu_fetcher::ics::{to_icalendar, IcsOptions, MissingEnd};
+use serde_json::{json, Value};
+
+fn event(fields: Value) -> GetCalendarResponseElement {
+    common::event().fields(fields).build()
+}
+
+fn calendar() -> Vec<GetCalendarResponseElement> {
+    vec![
+        event(json!({})),
+        event(json!({
+            "id": "2",
+            "start": "2024-10-08T13:00:00",
+            "end": null,
+            "description": "TD\r\n\r\nSalle de réunion; bâtiment A, 2e étage<br />\r\n\r\nProbabilités",
+            "eventCategory": "TD",
+            "sites": ["NEUVILLE"],
+        })),
+        event(json!({
+            "id": "3",
+            "start": "2024-10-28T00:00:00",
+            "end": "2024-11-02T00:00:00",
+            "allDay": true,
+            "description": "Vacances de la Toussaint",
+            "eventCategory": "Vacances",
+            "sites": null,
+        })),
+    ]
+}
+
+fn options() -> IcsOptions<'static> {
+    IcsOptions::default()
+}
+
+fn export_time() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 10, 1, 12, 0, 0).unwrap()
+}
+
+#[test]
+fn default_options() {
+    insta::assert_snapshot!(to_icalendar(&calendar(), &options(), export_time()));
+}
+
+#[test]
+fn custom_options() {
+    let options = IcsOptions {
+        calendar_name: String::from("Emploi du temps, L3"),
+        title_template: Some(String::from("{module} ({category_name}) - {teachers}")),
+        include_description: false,
+        missing_end: MissingEnd::Skip,
+        sites: None,
+        ..options()
+    };
+    insta::assert_snapshot!(to_icalendar(&calendar(), &options, export_time()));
+}
+
+#[test]
+fn missing_end_duration() {
+    let options = IcsOptions {
+        missing_end: MissingEnd::Duration(TimeDelta::minutes(90)),
+        ..options()
+    };
+    let ics = to_icalendar(&calendar()[1..2], &options, export_time());
+    assert!(ics.contains("DTSTART:20241008T110000Z\r\n"));
+    assert!(ics.contains("DTEND:20241008T123000Z\r\n"));
+}
+
+#[test]
+fn long_lines_are_folded() {
+    let description = "é".repeat(100);
+    let ics = to_icalendar(
+        &[event(json!({ "description": description }))],
+        &options(),
+        export_time(),
+    );
+    for line in ics.split("\r\n") {
+        assert!(line.len() <= 75, "{line:?} is too long");
+    }
+    let unfolded = ics.replace("\r\n ", "");
+    assert!(unfolded.contains(&format!("DESCRIPTION:{description}\r\n")));
+}
+
+#[test]
+fn text_values_are_escaped() {
+    let event = event(json!({
+        "description": "TD\r\n\r\nSalle A; B, C\\D",
+        "eventCategory": "TD",
+    }));
+    let ics = to_icalendar(&[event], &options(), export_time());
+    assert!(ics.contains(r"DESCRIPTION:TD\nSalle A\; B\, C\\D"));
+    assert!(!ics.replace("\r\n", "").contains('\n'));
+}
@@ -0,0 +1,749 @@
+use chrono::{Datelike as _, NaiveDate};
+use cyu_fetcher::calendar::{
+    CalendarSource, CalendarView, ChunkSize, ColorBy, GetAllQuery, GetCalendarQuery,
+    GetLimitsQuery, GetMergedCalendarQuery, GetRangeQuery, RangeOptions, ResourceType,
+};
+use cyu_fetcher::sites::SiteRegistry;
+use cyu_fetcher::utils::{AcademicYear, CyuDate};
+use cyu_fetcher::{Error, Fetcher, SessionCookies};
+use cyu_mock::{Event, Fixtures, MockServer, Resource};
+use std::time::Duration;
+
+fn datetime(day: u32, hour: u32) -> chrono::NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 10, day)
+        .unwrap()
+        .and_hms_opt(hour, 0, 0)
+        .unwrap()
+}
+
+fn fixtures() -> Fixtures {
+    Fixtures {
+        events: vec![
+            Event::new(
+                "1",
+                datetime(7, 8),
+                datetime(7, 10),
+                "CM",
+                &["ANALYSE", "E213", "DUPONT JEAN"],
+            )
+            .with_sites(&["SAINT MARTIN"]),
+            Event::new(
+                "2",
+                datetime(8, 13),
+                datetime(8, 15),
+                "TD",
+                &["PROBABILITES", "A101", "MARTIN PAUL"],
+            )
+            .with_sites(&["CHENES"]),
+        ],
+        ..Default::default()
+    }
+}
+
+async fn setup() -> (MockServer, Fetcher) {
+    let server = MockServer::start(fixtures()).await;
+    let fetcher = Fetcher::builder()
+        .base_url(server.base_url())
+        .build()
+        .unwrap();
+    (server, fetcher)
+}
+
+async fn login(fetcher: &Fetcher) -> SessionCookies {
+    fetcher
+        .login("e-student".into(), "password".into())
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn login_and_get_infos() {
+    let (_server, fetcher) = setup().await;
+    let token = login(&fetcher).await;
+    let infos = fetcher.get_infos(&token).await.unwrap();
+    assert_eq!(infos.federation_id, "22001234");
+    assert_eq!(infos.display_name, "STUDENT Jane");
+}
+
+#[tokio::test]
+async fn login_keeps_only_cookie_values() {
+    let (_server, fetcher) = setup().await;
+    let token = login(&fetcher).await;
+    assert!(token.get(".AspNetCore.Cookies").is_some());
+    let header = token.header();
+    assert!(!header.to_lowercase().contains("path="), "{header}");
+    assert!(!header.to_lowercase().contains("httponly"), "{header}");
+
+    let saved = serde_json::to_string(&token).unwrap();
+    let restored: SessionCookies = serde_json::from_str(&saved).unwrap();
+    assert_eq!(restored, token);
+    assert!(fetcher.get_infos(&restored).await.is_ok());
+}
+
+#[tokio::test]
+async fn renewed_session_cookies_are_kept() {
+    let (server, fetcher) = setup().await;
+    let token = login(&fetcher).await;
+    let first = token.get(".AspNetCore.Cookies").unwrap();
+    server.update(|fixtures| fixtures.renew_sessions = true);
+
+    // Each request gets the cookie set by the previous one, the old one
+    // being forgotten by the server
+    let clone = token.clone();
+    assert!(fetcher.get_infos(&clone).await.is_ok());
+    let renewed = token.get(".AspNetCore.Cookies").unwrap();
+    assert_ne!(renewed, first);
+    assert!(fetcher.get_infos(&token).await.is_ok());
+
+    let saved = serde_json::to_string(&token).unwrap();
+    assert!(!saved.contains(&renewed), "{saved}");
+}
+
+#[test]
+fn base_url_is_validated() {
+    let fetcher = Fetcher::builder()
+        .base_url("https://edt.example.fr/calendar/")
+        .build()
+        .unwrap();
+    assert_eq!(
+        fetcher.base_url().as_str(),
+        "https://edt.example.fr/calendar"
+    );
+
+    for base_url in ["edt.example.fr/calendar", "ftp://edt.example.fr/calendar"] {
+        let result = Fetcher::builder().base_url(base_url).build();
+        assert!(matches!(result, Err(Error::BaseUrl { .. })), "{base_url}");
+    }
+}
+
+#[tokio::test]
+async fn login_with_bad_credentials() {
+    let (_server, fetcher) = setup().await;
+    let result = fetcher.login("e-student".into(), "wrong".into()).await;
+    assert!(matches!(result, Err(Error::Unauthorized)));
+}
+
+#[tokio::test]
+async fn get_calendar_in_range() {
+    let (_server, fetcher) = setup().await;
+    let token = login(&fetcher).await;
+    let calendar = fetcher
+        .get_calendar(GetCalendarQuery {
+            id: "22001234".into(),
+            resource_type: ResourceType::Student,
+            token,
+            start: CyuDate::new(2024, 10, 7).unwrap(),
+            end: CyuDate::new(2024, 10, 7).unwrap(),
+            view: CalendarView::Day,
+            color_by: ColorBy::EventCategory,
+        })
+        .await
+        .unwrap();
+    assert_eq!(calendar.len(), 1);
+    assert_eq!(calendar[0].id(), "1");
+    assert_eq!(calendar[0].description(), "CM\nANALYSE\nE213\nDUPONT JEAN");
+    let location = SiteRegistry::embedded().locate(&calendar[0]).unwrap();
+    assert_eq!(location.unwrap().coordinates(), [49.043664, 2.0844198]);
+}
+
+#[tokio::test]
+async fn get_calendar_with_expired_session() {
+    let (server, fetcher) = setup().await;
+    let token = login(&fetcher).await;
+    server.expire_sessions();
+    let result = fetcher
+        .get_calendar(GetCalendarQuery {
+            id: "22001234".into(),
+            resource_type: ResourceType::Student,
+            token,
+            start: CyuDate::new(2024, 10, 7).unwrap(),
+            end: CyuDate::new(2024, 10, 11).unwrap(),
+            view: CalendarView::Week,
+            color_by: ColorBy::EventCategory,
+        })
+        .await;
+    assert!(matches!(result, Err(Error::Unauthorized)));
+}
+
+#[tokio::test]
+async fn get_infos_with_expired_session() {
+    let (server, fetcher) = setup().await;
+    let token = login(&fetcher).await;
+    server.expire_sessions();
+    // The login form served in place of the home page is not a layout change
+    let result = fetcher.get_infos(&token).await;
+    assert!(matches!(result, Err(Error::Unauthorized)), "{result:?}");
+}
+
+#[tokio::test]
+async fn get_limits_and_all() {
+    let (_server, fetcher) = setup().await;
+    let token = login(&fetcher).await;
+    let (start, end) = fetcher
+        .get_calendar_limits(GetLimitsQuery {
+            id: "22001234",
+            resource_type: ResourceType::Student,
+            token: &token,
+            date: Some(CyuDate::new(2024, 10, 7).unwrap()),
+        })
+        .await
+        .unwrap();
+    assert_eq!(start, CyuDate::new(2024, 9, 2).unwrap());
+    assert_eq!(end, CyuDate::new(2025, 8, 31).unwrap());
+
+    let calendar = fetcher
+        .get_all_calendar(GetAllQuery {
+            id: "22001234".into(),
+            token,
+            color_by: ColorBy::EventCategory,
+        })
+        .await
+        .unwrap();
+    assert!(calendar.is_complete());
+    assert_eq!(calendar.events.len(), 2);
+}
+
+#[tokio::test]
+async fn get_limits_with_expired_session() {
+    let (server, fetcher) = setup().await;
+    let token = login(&fetcher).await;
+    server.expire_sessions();
+    let result = fetcher
+        .get_calendar_limits(GetLimitsQuery {
+            id: "22001234",
+            resource_type: ResourceType::Student,
+            token: &token,
+            date: None,
+        })
+        .await;
+    assert!(matches!(result, Err(Error::Unauthorized)));
+}
+
+#[tokio::test]
+async fn get_calendar_with_reversed_range() {
+    let (_server, fetcher) = setup().await;
+    let token = login(&fetcher).await;
+    let result = fetcher
+        .get_calendar(GetCalendarQuery {
+            id: "22001234".into(),
+            resource_type: ResourceType::Student,
+            token,
+            start: CyuDate::new(2024, 10, 11).unwrap(),
+            end: CyuDate::new(2024, 10, 7).unwrap(),
+            view: CalendarView::Week,
+            color_by: ColorBy::EventCategory,
+        })
+        .await;
+    assert!(matches!(result, Err(Error::DateRange { .. })));
+}
+
+#[tokio::test]
+async fn unreachable_server() {
+    let fetcher = Fetcher::builder()
+        .base_url("http://127.0.0.1:1/calendar")
+        .build()
+        .unwrap();
+    let result = fetcher.login("e-student".into(), "password".into()).await;
+    assert!(matches!(result, Err(Error::Network(_))));
+}
+
+#[tokio::test]
+async fn get_limits_picks_academic_year() {
+    let (server, fetcher) = setup().await;
+    server.update(|fixtures| {
+        fixtures.date_extents = (
+            NaiveDate::from_ymd_opt(2023, 9, 4).unwrap(),
+            NaiveDate::from_ymd_opt(2026, 7, 10).unwrap(),
+        )
+    });
+    let token = login(&fetcher).await;
+    let limits = |date| GetLimitsQuery {
+        id: "22001234",
+        resource_type: ResourceType::Student,
+        token: &token,
+        date: Some(date),
+    };
+
+    let current = fetcher
+        .get_calendar_limits(limits(CyuDate::new(2025, 1, 10).unwrap()))
+        .await
+        .unwrap();
+    assert_eq!(
+        current,
+        (
+            CyuDate::new(2024, 9, 1).unwrap(),
+            CyuDate::new(2025, 8, 31).unwrap()
+        )
+    );
+
+    let before = fetcher
+        .get_calendar_limits(limits(CyuDate::new(2022, 5, 1).unwrap()))
+        .await
+        .unwrap();
+    assert_eq!(before.0, CyuDate::new(2023, 9, 4).unwrap());
+
+    let after = fetcher
+        .get_calendar_limits(limits(CyuDate::new(2027, 1, 1).unwrap()))
+        .await
+        .unwrap();
+    assert_eq!(after.1, CyuDate::new(2026, 7, 10).unwrap());
+
+    let years = fetcher
+        .get_academic_years(limits(CyuDate::new(2025, 1, 10).unwrap()))
+        .await
+        .unwrap();
+    assert_eq!(
+        years,
+        vec![
+            AcademicYear {
+                start: CyuDate::new(2023, 9, 4).unwrap(),
+                end: CyuDate::new(2024, 8, 31).unwrap(),
+            },
+            AcademicYear::starting_in(2024),
+            AcademicYear {
+                start: CyuDate::new(2025, 9, 1).unwrap(),
+                end: CyuDate::new(2026, 7, 10).unwrap(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn chunks_are_aligned_on_weeks_and_months() {
+    let date = |month, day| CyuDate::new(2024, month, day).unwrap();
+    assert_eq!(
+        ChunkSize::Week.split(&date(10, 2), &date(10, 16)),
+        vec![
+            (date(10, 2), date(10, 6)),
+            (date(10, 7), date(10, 13)),
+            (date(10, 14), date(10, 16)),
+        ]
+    );
+    assert_eq!(
+        ChunkSize::Month.split(&date(9, 15), &date(11, 3)),
+        vec![
+            (date(9, 15), date(9, 30)),
+            (date(10, 1), date(10, 31)),
+            (date(11, 1), date(11, 3)),
+        ]
+    );
+    assert_eq!(
+        ChunkSize::Month.split(&date(10, 5), &date(10, 5)),
+        vec![(date(10, 5), date(10, 5))]
+    );
+}
+
+fn range_query(token: SessionCookies, chunk_size: ChunkSize) -> GetRangeQuery {
+    GetRangeQuery {
+        id: "22001234".into(),
+        resource_type: ResourceType::Student,
+        token,
+        start: CyuDate::new(2024, 9, 2).unwrap(),
+        end: CyuDate::new(2024, 11, 30).unwrap(),
+        color_by: ColorBy::EventCategory,
+        options: RangeOptions {
+            chunk_size,
+            retry_delay: Duration::from_millis(10),
+            ..Default::default()
+        },
+    }
+}
+
+#[tokio::test]
+async fn get_range_retries_transient_failures() {
+    let (server, fetcher) = setup().await;
+    server.update(|fixtures| fixtures.transient_failures = 2);
+    let token = login(&fetcher).await;
+    let calendar = fetcher
+        .get_calendar_range(range_query(token, ChunkSize::Month))
+        .await
+        .unwrap();
+    assert!(calendar.is_complete());
+    assert_eq!(calendar.events.len(), 2);
+    assert_eq!(server.hits("/Home/GetCalendarData"), 3 + 2);
+}
+
+#[tokio::test]
+async fn get_range_reports_failed_chunks() {
+    let (server, fetcher) = setup().await;
+    server.update(|fixtures| {
+        fixtures.failing_days = vec![NaiveDate::from_ymd_opt(2024, 10, 8).unwrap()];
+        fixtures.events.push(Event::new(
+            "3",
+            datetime(15, 8),
+            datetime(15, 10),
+            "CM",
+            &["ANALYSE"],
+        ));
+    });
+    let token = login(&fetcher).await;
+    let calendar = fetcher
+        .get_calendar_range(range_query(token, ChunkSize::Week))
+        .await
+        .unwrap();
+    let ids = calendar
+        .events
+        .iter()
+        .map(|e| e.id().as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec!["3"]);
+    assert_eq!(calendar.failures.len(), 1);
+    assert_eq!(
+        calendar.failures[0].start,
+        CyuDate::new(2024, 10, 7).unwrap()
+    );
+    assert!(matches!(calendar.failures[0].error, Error::Status { .. }));
+}
+
+#[tokio::test]
+async fn get_range_fails_when_every_chunk_fails() {
+    let (server, fetcher) = setup().await;
+    server.update(|fixtures| fixtures.transient_failures = usize::MAX);
+    let token = login(&fetcher).await;
+    let result = fetcher
+        .get_calendar_range(range_query(token, ChunkSize::Month))
+        .await;
+    assert!(matches!(result, Err(Error::Status { .. })));
+}
+
+#[tokio::test]
+async fn get_range_deduplicates_events_across_chunks() {
+    let (server, fetcher) = setup().await;
+    server.update(|fixtures| {
+        // Sunday night to Monday morning, so it is part of two weekly chunks
+        fixtures.events.push(Event::new(
+            "3",
+            datetime(13, 22),
+            datetime(14, 2),
+            "Réunion",
+            &[],
+        ))
+    });
+    let token = login(&fetcher).await;
+    let calendar = fetcher
+        .get_calendar_range(range_query(token, ChunkSize::Week))
+        .await
+        .unwrap();
+    let ids = calendar
+        .events
+        .iter()
+        .map(|e| e.id().as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec!["1", "2", "3"]);
+}
+
+#[tokio::test]
+async fn get_range_with_expired_session() {
+    let (server, fetcher) = setup().await;
+    let token = login(&fetcher).await;
+    server.expire_sessions();
+    let result = fetcher
+        .get_calendar_range(range_query(token, ChunkSize::Week))
+        .await;
+    assert!(matches!(result, Err(Error::Unauthorized)));
+}
+
+#[tokio::test]
+async fn get_calendar_of_a_room() {
+    let (server, fetcher) = setup().await;
+    server.update(|fixtures| {
+        fixtures.resources.push(Resource {
+            resource_type: 102,
+            id: "E213".into(),
+            name: "E213 [SAINT MARTIN]".into(),
+            events: vec![Event::new(
+                "10",
+                datetime(9, 8),
+                datetime(9, 12),
+                "TP",
+                &["BASES DE DONNEES", "E213"],
+            )],
+        })
+    });
+    let token = login(&fetcher).await;
+    let query = |id: &str, resource_type| GetCalendarQuery {
+        id: id.into(),
+        resource_type,
+        token: token.clone(),
+        start: CyuDate::new(2024, 10, 7).unwrap(),
+        end: CyuDate::new(2024, 10, 13).unwrap(),
+        view: CalendarView::Week,
+        color_by: ColorBy::EventCategory,
+    };
+
+    let room = fetcher
+        .get_calendar(query("E213", ResourceType::Room))
+        .await
+        .unwrap();
+    assert_eq!(room.len(), 1);
+    assert_eq!(room[0].id(), "10");
+
+    let group = fetcher
+        .get_calendar(query("E213", ResourceType::Group))
+        .await
+        .unwrap();
+    assert!(group.is_empty());
+
+    let (start, end) = fetcher
+        .get_calendar_limits(GetLimitsQuery {
+            id: "E213",
+            resource_type: ResourceType::Room,
+            token: &token,
+            date: Some(CyuDate::new(2024, 10, 7).unwrap()),
+        })
+        .await
+        .unwrap();
+    assert_eq!(start, CyuDate::new(2024, 9, 2).unwrap());
+    assert_eq!(end, CyuDate::new(2025, 8, 31).unwrap());
+}
+
+fn room(id: &str, name: &str) -> Resource {
+    Resource {
+        resource_type: 102,
+        id: id.into(),
+        name: name.into(),
+        events: Vec::new(),
+    }
+}
+
+#[tokio::test]
+async fn search_rooms() {
+    let (server, fetcher) = setup().await;
+    server.update(|fixtures| {
+        fixtures.resources.push(room("E213", "E213 [SAINT MARTIN]"));
+        fixtures.resources.push(room("A101", "A101 [CHENES]"));
+        fixtures.resources.push(Resource {
+            resource_type: 101,
+            id: "1234".into(),
+            name: "MARTIN PAUL".into(),
+            events: Vec::new(),
+        });
+    });
+    let token = login(&fetcher).await;
+
+    let rooms = fetcher
+        .search_resources(&token, ResourceType::Room, "saint")
+        .await
+        .unwrap();
+    assert_eq!(rooms.len(), 1);
+    assert_eq!(rooms[0].id, "E213");
+    assert_eq!(rooms[0].display_name, "E213 [SAINT MARTIN]");
+    assert_eq!(rooms[0].resource_type, ResourceType::Room);
+    assert_eq!(rooms[0].department.as_deref(), Some("CY Tech"));
+
+    let staff = fetcher
+        .search_resources(&token, ResourceType::Staff, "martin")
+        .await
+        .unwrap();
+    assert_eq!(staff.len(), 1);
+    assert_eq!(staff[0].id, "1234");
+
+    let none = fetcher
+        .search_resources(&token, ResourceType::Group, "")
+        .await
+        .unwrap();
+    assert!(none.is_empty());
+}
+
+#[tokio::test]
+async fn search_goes_through_every_page() {
+    let (server, fetcher) = setup().await;
+    server.update(|fixtures| {
+        for number in 0..120 {
+            let id = format!("E{number:03}");
+            let name = format!("{id} [SAINT MARTIN]");
+            fixtures.resources.push(room(&id, &name));
+        }
+    });
+    let token = login(&fetcher).await;
+    let rooms = fetcher
+        .search_resources(&token, ResourceType::Room, "saint")
+        .await
+        .unwrap();
+    assert_eq!(rooms.len(), 120);
+    assert_eq!(rooms[119].id, "E119");
+    assert_eq!(server.hits("/Home/ReadResourceListItems"), 3);
+}
+
+#[tokio::test]
+async fn search_stops_when_pages_repeat() {
+    let (server, fetcher) = setup().await;
+    server.update(|fixtures| {
+        fixtures.unpaged_search = true;
+        for number in 0..120 {
+            let id = format!("E{number:03}");
+            let name = format!("{id} [SAINT MARTIN]");
+            fixtures.resources.push(room(&id, &name));
+        }
+    });
+    let token = login(&fetcher).await;
+    let rooms = fetcher
+        .search_resources(&token, ResourceType::Room, "saint")
+        .await
+        .unwrap();
+    assert_eq!(rooms.len(), 50);
+    assert_eq!(server.hits("/Home/ReadResourceListItems"), 2);
+}
+
+#[tokio::test]
+async fn search_with_expired_session() {
+    let (server, fetcher) = setup().await;
+    let token = login(&fetcher).await;
+    server.expire_sessions();
+    let result = fetcher
+        .search_resources(&token, ResourceType::Room, "E2")
+        .await;
+    assert!(matches!(result, Err(Error::Unauthorized)));
+}
+
+fn group(id: &str, events: Vec<Event>) -> Resource {
+    Resource {
+        resource_type: 103,
+        id: id.into(),
+        name: format!("GROUPE {id}"),
+        events,
+    }
+}
+
+#[tokio::test]
+async fn merge_group_calendars() {
+    let (server, fetcher) = setup().await;
+    let shared = Event::new("20", datetime(7, 8), datetime(7, 10), "CM", &["ANALYSE"]);
+    server.update(|fixtures| {
+        fixtures.resources.push(group(
+            "ING1-GM1",
+            vec![
+                shared.clone(),
+                Event::new("21", datetime(8, 8), datetime(8, 10), "TD", &["ANALYSE"]),
+            ],
+        ));
+        fixtures.resources.push(group(
+            "OPT-JAPONAIS",
+            vec![
+                Event::new("22", datetime(7, 13), datetime(7, 15), "TD", &["JAPONAIS"]),
+                shared,
+            ],
+        ));
+    });
+    let token = login(&fetcher).await;
+    let main = CalendarSource::new(ResourceType::Group, "ING1-GM1");
+    let option = CalendarSource::new(ResourceType::Group, "OPT-JAPONAIS");
+
+    let merged = fetcher
+        .get_merged_calendar(GetMergedCalendarQuery {
+            sources: vec![main.clone(), option.clone()],
+            token,
+            start: CyuDate::new(2024, 10, 7).unwrap(),
+            end: CyuDate::new(2024, 10, 13).unwrap(),
+            view: CalendarView::Week,
+            color_by: ColorBy::EventCategory,
+        })
+        .await
+        .unwrap();
+    let summary = merged
+        .iter()
+        .map(|merged| (merged.event.id().as_str(), merged.sources.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            ("20", vec![main.clone(), option.clone()]),
+            ("22", vec![option]),
+            ("21", vec![main]),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn merge_fails_with_expired_session() {
+    let (server, fetcher) = setup().await;
+    let token = login(&fetcher).await;
+    server.expire_sessions();
+    let result = fetcher
+        .get_merged_calendar(GetMergedCalendarQuery {
+            sources: vec![
+                CalendarSource::new(ResourceType::Student, "22001234"),
+                CalendarSource::new(ResourceType::Group, "ING1-GM1"),
+            ],
+            token,
+            start: CyuDate::new(2024, 10, 7).unwrap(),
+            end: CyuDate::new(2024, 10, 13).unwrap(),
+            view: CalendarView::Week,
+            color_by: ColorBy::EventCategory,
+        })
+        .await;
+    assert!(matches!(result, Err(Error::Unauthorized)));
+}
+
+#[test]
+fn resource_type_codes() {
+    assert_eq!(ResourceType::Room.code(), 102);
+    assert_eq!(ResourceType::from_code(101), Some(ResourceType::Staff));
+    assert_eq!(ResourceType::from_code(42), None);
+    assert_eq!(ResourceType::default(), ResourceType::Student);
+}
+
+#[tokio::test]
+async fn get_student_profile() {
+    let (server, fetcher) = setup().await;
+    server.update(|fixtures| {
+        let lecture = |id: &str, day| {
+            Event::new(
+                id,
+                datetime(day, 8),
+                datetime(day, 10),
+                "CM",
+                &["ANALYSE", "L3 INFORMATIQUE", "DUPONT Jean"],
+            )
+        };
+        let tutorial = |id: &str, day, group| {
+            Event::new(
+                id,
+                datetime(day, 13),
+                datetime(day, 15),
+                "TD",
+                &["PROBABILITES", group, "A101"],
+            )
+        };
+        fixtures.events = vec![
+            lecture("1", 7),
+            lecture("2", 14),
+            lecture("3", 21),
+            tutorial("4", 8, "L3 INFO TD1"),
+            tutorial("5", 15, "L3 INFO TD1"),
+            // Past the weeks the groups are read from
+            Event::new(
+                "6",
+                datetime(7, 8).with_month(12).unwrap(),
+                datetime(7, 10).with_month(12).unwrap(),
+                "TD",
+                &["PROBABILITES", "L3 INFO TD2"],
+            ),
+        ];
+    });
+    let token = login(&fetcher).await;
+
+    let profile = fetcher
+        .get_profile(&token, Some(CyuDate::new(2024, 10, 7).unwrap()))
+        .await
+        .unwrap();
+    assert_eq!(profile.federation_id, "22001234");
+    assert_eq!(profile.display_name, "STUDENT Jane");
+    assert_eq!(profile.groups, ["L3 INFORMATIQUE", "L3 INFO TD1"]);
+    assert_eq!(profile.programme.as_deref(), Some("L3 INFORMATIQUE"));
+    assert_eq!(profile.department.as_deref(), Some("CY Tech"));
+    assert_eq!(
+        profile.date_extents,
+        (
+            CyuDate::new(2024, 9, 2).unwrap(),
+            CyuDate::new(2025, 8, 31).unwrap(),
+        )
+    );
+
+    // Once the calendar is over, the groups are read from its last weeks
+    let profile = fetcher
+        .get_profile(&token, Some(CyuDate::new(2026, 1, 5).unwrap()))
+        .await
+        .unwrap();
+    assert!(profile.groups.is_empty() && profile.programme.is_none());
+}
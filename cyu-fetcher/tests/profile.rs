@@ -0,0 +1,182 @@
+use chrono::{Days, NaiveDate, TimeZone as _, Utc};
+use cyu_fetcher::calendar::GetCalendarResponseElement;
+use cyu_fetcher::ics::{to_icalendar, IcsOptions};
+use cyu_fetcher::profile::AuthConfig;
+use cyu_fetcher::{Fetcher, InstanceProfile};
+use cyu_mock::{Fixtures, MockServer};
+use serde_json::json;
+
+const PROFILE: &str = r#"
+name = "UQAM"
+base_url = "https://edt.example.ca/calendar"
+timezone = "America/Toronto"
+
+[auth]
+kind = "cas"
+server_url = "https://cas.example.ca/cas"
+
+[[sites]]
+name = "JUDITH-JASMIN"
+coordinates = [45.5148, -73.5612]
+room_prefixes = ["J-"]
+"#;
+
+/// A lecture every Monday at 8:30, local time of the instance.
+fn lectures() -> Vec<GetCalendarResponseElement> {
+    lectures_on(["2024-10-28", "2024-11-04", "2024-11-11"])
+}
+
+fn lectures_on<'a>(dates: impl IntoIterator<Item = &'a str>) -> Vec<GetCalendarResponseElement> {
+    dates
+        .into_iter()
+        .enumerate()
+        .map(|(id, date)| {
+            serde_json::from_value(json!({
+                "id": id.to_string(),
+                "start": format!("{date}T08:30:00"),
+                "end": format!("{date}T10:00:00"),
+                "allDay": false,
+                "description": "CM\r\n\r\nJ-M400<br />\r\n\r\nAlgorithmique",
+                "backgroundColor": "#7D4F72",
+                "department": "Informatique",
+                "faculty": null,
+                "eventCategory": "CM",
+                "sites": ["JUDITH-JASMIN"],
+                "modules": null,
+            }))
+            .unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn defaults() {
+    let profile = InstanceProfile::from_toml(
+        "name = \"Exemple\"\nbase_url = \"https://edt.example.fr/calendar\"",
+    )
+    .unwrap();
+    assert_eq!(profile.auth, AuthConfig::Ldap);
+    assert_eq!(profile.timezone, chrono_tz::Europe::Paris);
+    assert!(profile.sites.sites().is_empty());
+
+    let cyu = InstanceProfile::cyu();
+    assert_eq!(cyu.base_url.as_str(), cyu_fetcher::DEFAULT_BASE_URL);
+    assert!(cyu.sites.site("CHENES").is_some());
+}
+
+#[test]
+fn other_instance() {
+    let profile = InstanceProfile::from_toml(PROFILE).unwrap();
+    assert_eq!(
+        profile.auth,
+        AuthConfig::Cas {
+            server_url: "https://cas.example.ca/cas".into(),
+            service_path: None,
+        }
+    );
+    assert_eq!(profile.timezone, chrono_tz::America::Toronto);
+    assert_eq!(
+        profile.sites.room("J-M400").unwrap().site.name,
+        "JUDITH-JASMIN"
+    );
+
+    let error = InstanceProfile::from_toml(&PROFILE.replace("America/Toronto", "Mars/Olympus"))
+        .unwrap_err();
+    assert!(error.to_string().contains("unknown timezone"), "{error}");
+
+    let error = InstanceProfile::from_toml(&PROFILE.replace("https://", "")).unwrap_err();
+    assert!(error.to_string().contains("invalid base URL"), "{error}");
+}
+
+#[test]
+fn export_in_instance_timezone() {
+    let profile = InstanceProfile::from_toml(PROFILE).unwrap();
+    let options = profile.ics_options();
+    let export_time = Utc.with_ymd_and_hms(2024, 10, 1, 12, 0, 0).unwrap();
+    let ics = to_icalendar(&lectures(), &options, export_time);
+    assert!(ics.contains("X-WR-CALNAME:UQAM Calendar"));
+    // Summer time until November 3rd, UTC-4 then UTC-5
+    assert!(ics.contains("DTSTART:20241028T123000Z"));
+    assert!(ics.contains("DTSTART:20241104T133000Z"));
+    assert!(ics.contains("GEO:45.5148;-73.5612"));
+
+    let options = IcsOptions {
+        collapse_recurrences: true,
+        ..options
+    };
+    let ics = to_icalendar(&lectures(), &options, export_time);
+    assert!(ics.contains("DTSTART;TZID=America/Toronto:20241028T083000"));
+    for line in [
+        "TZID:America/Toronto",
+        "TZOFFSETFROM:-0500\r\nTZOFFSETTO:-0400\r\nTZNAME:EDT\r\nDTSTART:20240310T020000\r\nRRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=2SU\r\n",
+        "TZOFFSETFROM:-0400\r\nTZOFFSETTO:-0500\r\nTZNAME:EST\r\nDTSTART:20241103T020000\r\nRRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=1SU\r\n",
+    ] {
+        assert!(ics.contains(line), "{line} missing from\n{ics}");
+    }
+
+    // Without daylight saving time
+    let options = IcsOptions {
+        timezone: chrono_tz::Asia::Tokyo,
+        ..options
+    };
+    let ics = to_icalendar(&lectures(), &options, export_time);
+    assert!(ics.contains(
+        "BEGIN:STANDARD\r\nTZOFFSETFROM:+0900\r\nTZOFFSETTO:+0900\r\nTZNAME:JST\r\nDTSTART:19700101T000000\r\nEND:STANDARD"
+    ), "{ics}");
+}
+
+#[test]
+fn timezone_rules_over_the_exported_years() {
+    let profile = InstanceProfile::from_toml(PROFILE).unwrap();
+    let options = IcsOptions {
+        collapse_recurrences: true,
+        ..profile.ics_options()
+    };
+    // Daylight saving time was extended in 2007
+    let dates = (0..80)
+        .map(|week| {
+            let date = NaiveDate::from_ymd_opt(2005, 10, 24).unwrap() + Days::new(7 * week);
+            date.to_string()
+        })
+        .collect::<Vec<_>>();
+    let export_time = Utc.with_ymd_and_hms(2005, 10, 1, 12, 0, 0).unwrap();
+    let ics = to_icalendar(
+        &lectures_on(dates.iter().map(String::as_str)),
+        &options,
+        export_time,
+    );
+    assert_eq!(ics.matches("RRULE:FREQ=WEEKLY").count(), 1, "{ics}");
+    for line in [
+        "TZOFFSETTO:-0400\r\nTZNAME:EDT\r\nDTSTART:20050403T020000\r\nRRULE:FREQ=YEARLY;BYMONTH=4;BYDAY=1SU;UNTIL=20060402T070000Z\r\nEND:DAYLIGHT",
+        "TZOFFSETTO:-0500\r\nTZNAME:EST\r\nDTSTART:20051030T020000\r\nRRULE:FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU;UNTIL=20061029T060000Z\r\nEND:STANDARD",
+        "TZOFFSETTO:-0400\r\nTZNAME:EDT\r\nDTSTART:20070311T020000\r\nRRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=2SU\r\nEND:DAYLIGHT",
+        "TZOFFSETTO:-0500\r\nTZNAME:EST\r\nDTSTART:20071104T020000\r\nRRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=1SU\r\nEND:STANDARD",
+    ] {
+        assert!(ics.contains(line), "{line} missing from\n{ics}");
+    }
+}
+
+#[tokio::test]
+async fn fetcher_from_profile() {
+    let server = MockServer::start(Fixtures::default()).await;
+    let profile = InstanceProfile {
+        base_url: server.base_url().parse().unwrap(),
+        auth: AuthConfig::Cas {
+            server_url: server.cas_url(),
+            service_path: None,
+        },
+        timezone: chrono_tz::America::Toronto,
+        ..InstanceProfile::from_toml(PROFILE).unwrap()
+    };
+    let fetcher = Fetcher::builder().profile(&profile).build().unwrap();
+    assert_eq!(fetcher.timezone(), chrono_tz::America::Toronto);
+    let token = fetcher
+        .login("e-student".into(), "password".into())
+        .await
+        .unwrap();
+    assert_eq!(
+        fetcher.get_infos(&token).await.unwrap().federation_id,
+        "22001234"
+    );
+    assert_eq!(server.hits("/CasLogin"), 1);
+}
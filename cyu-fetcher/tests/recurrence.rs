@@ -0,0 +1,189 @@
+mod common;
+
+use chrono::{DateTime, NaiveDateTime, TimeZone as _, Utc};
+use cyu_fetcher::calendar::GetCalendarResponseElement;
+use cyu_fetcher::ics::{collapse_weekly, to_icalendar, IcsOptions, Recurrence};
+use cyu_fetcher::utils::CyuDateTime;
+use serde_json::{json, Value};
+
+fn export_time() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 10, 1, 12, 0, 0).unwrap()
+}
+
+fn event(id: &str, start: &str, end: Option<&str>, fields: Value) -> GetCalendarResponseElement {
+    common::event()
+        .id(id)
+        .start(start)
+        .end(end)
+        .fields(fields)
+        .build()
+}
+
+/// The lecture every Monday morning, from `date`.
+fn lecture(id: &str, date: &str) -> GetCalendarResponseElement {
+    event(
+        id,
+        &format!("{date}T08:30:00"),
+        Some(&format!("{date}T10:00:00")),
+        json!({}),
+    )
+}
+
+/// A semester of lectures: no lecture during the holidays of October 28th,
+/// one moved to Tuesday, a break until January and an extra session.
+fn calendar() -> Vec<GetCalendarResponseElement> {
+    let tutorial = json!({
+        "description": "TD\r\n\r\nPC-CH-112<br />\r\n\r\nProbabilités",
+        "eventCategory": "TD",
+    });
+    vec![
+        lecture("1", "2024-10-07"),
+        lecture("2", "2024-10-14"),
+        lecture("3", "2024-10-21"),
+        lecture("4", "2024-11-05"),
+        lecture("5", "2024-11-11"),
+        lecture("6", "2024-11-18"),
+        lecture("7", "2025-01-13"),
+        lecture("8", "2025-01-20"),
+        event(
+            "9",
+            "2024-10-09T13:00:00",
+            Some("2024-10-09T15:00:00"),
+            tutorial.clone(),
+        ),
+        event(
+            "10",
+            "2024-10-16T13:00:00",
+            Some("2024-10-16T15:00:00"),
+            tutorial.clone(),
+        ),
+        event(
+            "11",
+            "2024-10-23T13:00:00",
+            Some("2024-10-23T15:00:00"),
+            tutorial,
+        ),
+        event("12", "2024-10-10T08:30:00", None, json!({})),
+        event(
+            "13",
+            "2024-10-28T00:00:00",
+            Some("2024-11-02T00:00:00"),
+            json!({ "allDay": true, "description": "Vacances", "eventCategory": "Vacances" }),
+        ),
+        // Same time, another room
+        event(
+            "14",
+            "2024-10-28T08:30:00",
+            Some("2024-10-28T10:00:00"),
+            json!({ "description": "CM\r\n\r\nPC-CH-101<br />\r\n\r\nArchitecture logicielle CM" }),
+        ),
+    ]
+}
+
+type Occurrence = (NaiveDateTime, Option<NaiveDateTime>, String, bool);
+
+fn occurrence(
+    event: &GetCalendarResponseElement,
+    start: &CyuDateTime,
+    end: Option<NaiveDateTime>,
+) -> Occurrence {
+    (**start, end, event.description(), *event.all_day())
+}
+
+/// Times and contents of the events the recurrences stand for.
+fn expand(recurrences: &[Recurrence]) -> Vec<Occurrence> {
+    let mut occurrences = Vec::new();
+    for recurrence in recurrences {
+        match recurrence {
+            Recurrence::Single(event) => occurrences.push(occurrence(
+                event,
+                event.start(),
+                event.end().as_ref().map(|end| **end),
+            )),
+            Recurrence::Weekly(series) => {
+                for start in series.occurrences() {
+                    let end = *start + series.duration();
+                    occurrences.push(occurrence(series.event, &start, Some(end)));
+                }
+            }
+        }
+    }
+    occurrences.sort();
+    occurrences
+}
+
+#[test]
+fn round_trip() {
+    let calendar = calendar();
+    let recurrences = collapse_weekly(&calendar);
+
+    let mut original = calendar
+        .iter()
+        .map(|event| occurrence(event, event.start(), event.end().as_ref().map(|end| **end)))
+        .collect::<Vec<_>>();
+    original.sort();
+    assert_eq!(expand(&recurrences), original);
+    // The lectures until the break, the tutorials and 5 other events
+    assert_eq!(recurrences.len(), 7);
+}
+
+#[test]
+fn exceptions() {
+    let calendar = calendar();
+    let recurrences = collapse_weekly(&calendar);
+    let series = recurrences
+        .iter()
+        .filter_map(|recurrence| match recurrence {
+            Recurrence::Weekly(series) => Some(series),
+            Recurrence::Single(_) => None,
+        })
+        .collect::<Vec<_>>();
+
+    let lectures = series
+        .iter()
+        .find(|series| series.event.id() == "1")
+        .unwrap();
+    assert_eq!(
+        *lectures.until,
+        *CyuDateTime::new(2024, 11, 18, 8, 30, 0).unwrap()
+    );
+    assert_eq!(
+        lectures.exdates,
+        [
+            CyuDateTime::new(2024, 10, 28, 8, 30, 0).unwrap(),
+            CyuDateTime::new(2024, 11, 4, 8, 30, 0).unwrap(),
+        ]
+    );
+    assert_eq!(
+        lectures.rdates,
+        [CyuDateTime::new(2024, 11, 5, 8, 30, 0).unwrap()]
+    );
+
+    let tutorials = series
+        .iter()
+        .find(|series| series.event.id() == "9")
+        .unwrap();
+    assert!(tutorials.exdates.is_empty() && tutorials.rdates.is_empty());
+    // Two lectures in January are not enough for a series
+    assert!(!series.iter().any(|series| series.event.id() == "7"));
+}
+
+#[test]
+fn export() {
+    let calendar = calendar();
+    let options = IcsOptions {
+        collapse_recurrences: true,
+        ..Default::default()
+    };
+    let ics = to_icalendar(&calendar, &options, export_time());
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 7);
+    insta::assert_snapshot!(ics);
+}
+
+#[test]
+fn not_collapsed_by_default() {
+    let calendar = calendar();
+    let ics = to_icalendar(&calendar, &IcsOptions::default(), export_time());
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), calendar.len());
+    assert!(!ics.contains("RRULE") && !ics.contains("BEGIN:VTIMEZONE"));
+}
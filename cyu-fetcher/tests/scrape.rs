@@ -0,0 +1,95 @@
+use cyu_fetcher::scrape::{cas_login_form, date_extents, federation_id, verification_token};
+use cyu_fetcher::utils::CyuDate;
+use cyu_fetcher::Error;
+
+const LDAP_LOGIN: &str = include_str!("fixtures/ldap_login.html");
+const HOME: &str = include_str!("fixtures/home.html");
+const HOME_MONTH: &str = include_str!("fixtures/home_month.html");
+const CAS_LOGIN: &str = include_str!("fixtures/cas_login.html");
+
+fn layout_fragment(error: Error) -> String {
+    assert!(error.is_layout_change(), "{error:?}");
+    let fragment = error.fragment().expect("a fragment").to_owned();
+    assert!(!error.to_string().contains(&fragment), "{error}");
+    fragment
+}
+
+#[test]
+fn login_page() {
+    assert_eq!(
+        verification_token(LDAP_LOGIN).unwrap(),
+        "CfDJ8NfLqV2k_a-Zx0y9Q1w"
+    );
+    // Attribute order, quotes and self-closing do not matter
+    let reordered = r#"<form method="post">
+        <input type='hidden'
+               value="CfDJ8NfLqV2k"
+               name="__RequestVerificationToken">
+    </form>"#;
+    assert_eq!(verification_token(reordered).unwrap(), "CfDJ8NfLqV2k");
+}
+
+#[test]
+fn home_page() {
+    assert_eq!(federation_id(HOME).unwrap(), "22012345");
+    let reformatted = "<script>\n  let federationIdStr=\"22\\\"012345\";\n</script>";
+    assert_eq!(federation_id(reformatted).unwrap(), "22\"012345");
+    // Only scripts are read, not the text of the page
+    let in_text = "<p>var federationIdStr = '22012345';</p>";
+    assert!(federation_id(in_text).is_err());
+}
+
+#[test]
+fn month_view_page() {
+    let extents = (
+        CyuDate::new(2023, 9, 1).unwrap(),
+        CyuDate::new(2025, 8, 31).unwrap(),
+    );
+    assert_eq!(date_extents(HOME_MONTH).unwrap(), extents);
+    // Without CRLF line breaks, on a single line, with zero-based months
+    let reformatted = "<script>var dateExtents={latest:new Date(2025,7,31),\
+        earliest: new Date( 2023, 9 - 1, 1 )};</script>";
+    assert_eq!(date_extents(reformatted).unwrap(), extents);
+}
+
+#[test]
+fn cas_login_page() {
+    let form = cas_login_form(CAS_LOGIN).unwrap();
+    // Not the language form before it, and with its entities decoded
+    assert_eq!(
+        form.action.as_deref(),
+        Some("login?service=https%3A%2F%2Fservices-web.cyu.fr%2Fcalendar%2FCasLogin&renew=false")
+    );
+    let hidden = |name: &str, value: &str| (name.to_owned(), value.to_owned());
+    assert_eq!(
+        form.hidden_fields,
+        [
+            hidden("execution", "e1s1-3f9c0a1b2d"),
+            hidden("_eventId", "submit"),
+            hidden("geolocation", ""),
+        ]
+    );
+
+    let error = cas_login_form("<html><body><p>Service unavailable</p></body></html>").unwrap_err();
+    assert!(error.is_layout_change());
+}
+
+#[test]
+fn layout_changed() {
+    let login = LDAP_LOGIN.replace("__RequestVerificationToken", "__AntiForgery");
+    let error = verification_token(&login).unwrap_err();
+    assert!(error.is_layout_change());
+    assert!(layout_fragment(error).contains("<form action=\"/calendar/LdapLogin/Logon\""));
+
+    let month = HOME_MONTH.replace("new Date(2023, 9 - 1, 1)", "\"2023-09-01\"");
+    let error = date_extents(&month).unwrap_err();
+    assert!(error.to_string().contains("dateExtents variable not found"));
+    let fragment = layout_fragment(error);
+    assert!(fragment.contains("earliest: \"2023-09-01\""), "{fragment}");
+
+    let error = federation_id("<html><body>Maintenance</body></html>").unwrap_err();
+    assert_eq!(
+        layout_fragment(error),
+        "<html><body>Maintenance</body></html>"
+    );
+}
@@ -0,0 +1,146 @@
+use chrono::NaiveDate;
+use cyu_fetcher::calendar::{CalendarView, ColorBy};
+use cyu_fetcher::utils::CyuDate;
+use cyu_fetcher::{CredentialProvider, Credentials, Error, Fetcher, Identity, Session};
+use cyu_mock::{Event, Fixtures, MockServer};
+use futures::future::BoxFuture;
+
+const LOGON: &str = "/LdapLogin/Logon";
+
+async fn setup() -> (MockServer, Fetcher) {
+    let day = NaiveDate::from_ymd_opt(2024, 10, 7).unwrap();
+    let server = MockServer::start(Fixtures {
+        events: vec![Event::new(
+            "1",
+            day.and_hms_opt(8, 0, 0).unwrap(),
+            day.and_hms_opt(10, 0, 0).unwrap(),
+            "CM",
+            &["ANALYSE"],
+        )],
+        ..Default::default()
+    })
+    .await;
+    let fetcher = Fetcher::builder()
+        .base_url(server.base_url())
+        .build()
+        .unwrap();
+    (server, fetcher)
+}
+
+fn credentials(password: &str) -> Credentials {
+    Credentials {
+        username: "e-student".into(),
+        password: password.into(),
+    }
+}
+
+async fn get_day(session: &Session) -> Result<usize, Error> {
+    let day = CyuDate::new(2024, 10, 7).unwrap();
+    let calendar = session
+        .get_calendar(day.clone(), day, CalendarView::Day, ColorBy::EventCategory)
+        .await?;
+    Ok(calendar.len())
+}
+
+#[tokio::test]
+async fn logs_in_on_first_use() {
+    let (server, fetcher) = setup().await;
+    let session = Session::new(fetcher, credentials("password"));
+    assert_eq!(get_day(&session).await.unwrap(), 1);
+    assert_eq!(get_day(&session).await.unwrap(), 1);
+    assert_eq!(server.hits(LOGON), 1);
+
+    let infos = session.get_infos().await.unwrap();
+    assert_eq!(infos.federation_id, "22001234");
+    assert_eq!(infos.display_name, "STUDENT Jane");
+}
+
+#[tokio::test]
+async fn renews_expired_sessions() {
+    let (server, fetcher) = setup().await;
+    let session = Session::new(fetcher, credentials("password"));
+    let first = session.identity().await.unwrap();
+    server.expire_sessions();
+
+    assert_eq!(get_day(&session).await.unwrap(), 1);
+    assert_eq!(server.hits(LOGON), 2);
+    let second = session.identity().await.unwrap();
+    assert_ne!(first.token, second.token);
+    assert_eq!(first.federation_id, second.federation_id);
+}
+
+#[tokio::test]
+async fn follows_renewed_cookies() {
+    let (server, fetcher) = setup().await;
+    let session = Session::new(fetcher, credentials("password"));
+    let first = session.identity().await.unwrap();
+    let cookie = first.token.get(".AspNetCore.Cookies").unwrap();
+    server.update(|fixtures| fixtures.renew_sessions = true);
+
+    assert_eq!(get_day(&session).await.unwrap(), 1);
+    assert_eq!(get_day(&session).await.unwrap(), 1);
+    assert_eq!(server.hits(LOGON), 1);
+    let current = session.identity().await.unwrap();
+    assert_ne!(current.token.get(".AspNetCore.Cookies").unwrap(), cookie);
+}
+
+#[tokio::test]
+async fn concurrent_calls_renew_once() {
+    let (server, fetcher) = setup().await;
+    let session = Session::new(fetcher, credentials("password"));
+    session.identity().await.unwrap();
+    server.expire_sessions();
+
+    let (a, b, c) = tokio::join!(get_day(&session), get_day(&session), get_day(&session));
+    assert_eq!((a.unwrap(), b.unwrap(), c.unwrap()), (1, 1, 1));
+    assert_eq!(server.hits(LOGON), 2);
+}
+
+#[tokio::test]
+async fn resumed_identity_is_renewed() {
+    let (server, fetcher) = setup().await;
+    let session = Session::new(fetcher, credentials("password")).resume(Identity {
+        token: [(".AspNetCore.Cookies", "stale")].into_iter().collect(),
+        federation_id: String::from("22001234"),
+    });
+    assert_eq!(get_day(&session).await.unwrap(), 1);
+    assert_eq!(server.hits(LOGON), 1);
+}
+
+#[tokio::test]
+async fn saved_identity_is_resumed() {
+    let (server, fetcher) = setup().await;
+    let session = Session::new(fetcher.clone(), credentials("password"));
+    let saved = serde_json::to_string(&session.identity().await.unwrap()).unwrap();
+
+    let identity: Identity = serde_json::from_str(&saved).unwrap();
+    let resumed = Session::new(fetcher, credentials("password")).resume(identity);
+    assert_eq!(get_day(&resumed).await.unwrap(), 1);
+    assert_eq!(server.hits(LOGON), 1);
+}
+
+#[tokio::test]
+async fn refused_credentials_are_reported() {
+    let (_server, fetcher) = setup().await;
+    let session = Session::new(fetcher, credentials("wrong"));
+    assert!(matches!(get_day(&session).await, Err(Error::Unauthorized)));
+}
+
+struct NoCredentials;
+
+impl CredentialProvider for NoCredentials {
+    fn credentials(&self) -> BoxFuture<'_, Option<Credentials>> {
+        Box::pin(async { None })
+    }
+}
+
+#[tokio::test]
+async fn missing_credentials_are_reported() {
+    let (server, fetcher) = setup().await;
+    let session = Session::new(fetcher, NoCredentials);
+    assert!(matches!(get_day(&session).await, Err(Error::Unauthorized)));
+    assert_eq!(server.hits(LOGON), 0);
+
+    session.login_with(credentials("password")).await.unwrap();
+    assert_eq!(get_day(&session).await.unwrap(), 1);
+}
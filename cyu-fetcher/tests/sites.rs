@@ -0,0 +1,126 @@
+mod common;
+
+use cyu_fetcher::calendar::GetCalendarResponseElement;
+use cyu_fetcher::sites::{SiteRegistry, SiteRegistryError, UnknownSite};
+use serde_json::json;
+use std::io::Write as _;
+
+fn event(sites: &[&str], description: &str) -> GetCalendarResponseElement {
+    common::event()
+        .description(description)
+        .sites(sites)
+        .build()
+}
+
+const CUSTOM: &str = r#"
+[[sites]]
+name = "CHENES"
+coordinates = [49.0, 2.0]
+room_prefixes = ["PC-CH"]
+
+[[sites.buildings]]
+name = "Chênes 2"
+room_prefixes = ["PC-CH-2"]
+coordinates = [49.1, 2.1]
+address = "2 rue des Chênes"
+
+[[sites]]
+name = "NEUVILLE"
+coordinates = [49.01, 2.06]
+"#;
+
+#[test]
+fn embedded_sites() {
+    let sites = SiteRegistry::embedded();
+    for name in [
+        "PARC",
+        "CHENES",
+        "SAINT MARTIN",
+        "PORT",
+        "GENNEVILLIERS",
+        "PAU",
+    ] {
+        assert!(sites.site(name).is_some(), "{name} is missing");
+    }
+    assert_eq!(
+        sites.site("saint-germain-en-laye").unwrap().name,
+        "SAINT GERMAIN EN LAYE"
+    );
+}
+
+#[test]
+fn locate_by_site_or_room() {
+    let sites = SiteRegistry::embedded();
+    let by_site = sites.locate(&event(&["PORT"], "CM")).unwrap().unwrap();
+    assert_eq!(by_site.site.name, "PORT");
+
+    let by_room = sites
+        .locate(&event(&[], "CM\r\n\r\nPC-CH-329 [CHENES 1 - 329]"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(by_room.site.name, "CHENES");
+    assert_eq!(by_room.address(), Some("33 boulevard du Port, 95000 Cergy"));
+
+    assert_eq!(sites.locate(&event(&[], "CM")).unwrap(), None);
+}
+
+#[test]
+fn unknown_sites_are_reported() {
+    let sites = SiteRegistry::embedded();
+    let events = [
+        event(&["NEUVILLE"], "CM"),
+        event(&["CHENES"], "CM"),
+        event(&["NEUVILLE"], "TD"),
+    ];
+    assert_eq!(
+        sites.locate(&events[0]),
+        Err(UnknownSite {
+            name: "NEUVILLE".into()
+        })
+    );
+    assert_eq!(
+        sites.unknown_sites(&events).into_iter().collect::<Vec<_>>(),
+        vec!["NEUVILLE"]
+    );
+}
+
+#[test]
+fn overrides_and_buildings() {
+    let sites = SiteRegistry::embedded()
+        .clone()
+        .merge(SiteRegistry::from_toml(CUSTOM).unwrap());
+    assert_eq!(
+        sites.sites().len(),
+        SiteRegistry::embedded().sites().len() + 1
+    );
+    assert_eq!(sites.site("CHENES").unwrap().coordinates, [49.0, 2.0]);
+
+    let location = sites
+        .locate(&event(&["CHENES"], "CM\r\n\r\nPC-CH-204 [CHENES 2 - 204]"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(location.name(), "CHENES, Chênes 2");
+    assert_eq!(location.coordinates(), [49.1, 2.1]);
+    assert_eq!(location.address(), Some("2 rue des Chênes"));
+
+    let other_building = sites.room("PC-CH-329").unwrap();
+    assert_eq!(other_building.building, None);
+    assert_eq!(other_building.coordinates(), [49.0, 2.0]);
+}
+
+#[test]
+fn load_json_file() {
+    let mut file = std::env::temp_dir();
+    file.push(format!("cyu-sites-{}.json", std::process::id()));
+    let json = json!({ "sites": [{ "name": "PAU", "coordinates": [43.0, -0.3] }] });
+    std::fs::File::create(&file)
+        .unwrap()
+        .write_all(json.to_string().as_bytes())
+        .unwrap();
+    let sites = SiteRegistry::load(&file).unwrap();
+    std::fs::remove_file(&file).unwrap();
+    assert_eq!(sites.site("PAU").unwrap().coordinates, [43.0, -0.3]);
+
+    let result = SiteRegistry::load("sites.yaml");
+    assert!(matches!(result, Err(SiteRegistryError::Io { .. })));
+}
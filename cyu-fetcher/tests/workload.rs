@@ -0,0 +1,133 @@
+mod common;
+
+use chrono::TimeDelta;
+use cyu_fetcher::calendar::{compute_workload, EventKind, GetCalendarResponseElement, Hours};
+use cyu_fetcher::utils::{CyuDate, CyuDateTime};
+use serde_json::json;
+
+fn event(
+    start: &str,
+    end: Option<&str>,
+    category: &str,
+    module: &str,
+) -> GetCalendarResponseElement {
+    common::event()
+        .id(start)
+        .start(start)
+        .end(end)
+        .description(&format!("{category}\r\n\r\n{module}"))
+        .category(category)
+        .sites(&[])
+        .modules(&[module])
+        .build()
+}
+
+fn hours(done: i64, left: i64) -> Hours {
+    Hours {
+        done: TimeDelta::minutes(done),
+        left: TimeDelta::minutes(left),
+    }
+}
+
+fn calendar() -> Vec<GetCalendarResponseElement> {
+    vec![
+        event(
+            "2025-01-13T08:30:00",
+            Some("2025-01-13T10:00:00"),
+            "CM",
+            "Analyse",
+        ),
+        event(
+            "2025-01-14T13:00:00",
+            Some("2025-01-14T15:00:00"),
+            "TD",
+            "Analyse",
+        ),
+        event(
+            "2025-01-20T13:00:00",
+            Some("2025-01-20T15:00:00"),
+            "TD",
+            "Analyse",
+        ),
+        event(
+            "2025-02-03T10:00:00",
+            Some("2025-02-03T12:00:00"),
+            "TP",
+            "Réseaux",
+        ),
+        event("2025-02-04T10:00:00", None, "TD", "Réseaux"),
+        event(
+            "2025-02-10T08:00:00",
+            Some("2025-02-10T18:00:00"),
+            "Vacances",
+            "Réseaux",
+        ),
+    ]
+}
+
+#[test]
+fn hours_per_module_and_category() {
+    // During the second tutorial
+    let now = CyuDateTime::new(2025, 1, 14, 14, 0, 0).unwrap();
+    let workload = compute_workload(&calendar(), &now);
+
+    assert_eq!(workload.total, hours(150, 300));
+    assert_eq!(workload.category(&EventKind::Tutorial), hours(60, 180));
+    assert_eq!(workload.category(&EventKind::Holiday), Hours::default());
+
+    let modules = workload
+        .by_module
+        .iter()
+        .map(|module| module.module.as_deref().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(modules, vec!["Analyse", "Réseaux"]);
+    let analyse = workload.module("ANALYSE").unwrap();
+    assert_eq!(analyse.hours, hours(150, 180));
+    assert_eq!(analyse.category(&EventKind::Tutorial), hours(60, 180));
+    assert_eq!(analyse.category(&EventKind::Lecture), hours(90, 0));
+    assert_eq!(
+        workload
+            .module("Réseaux")
+            .unwrap()
+            .category(&EventKind::Practical),
+        hours(0, 120)
+    );
+}
+
+#[test]
+fn hours_per_week_and_semester() {
+    let now = CyuDateTime::new(2025, 9, 1, 0, 0, 0).unwrap();
+    let workload = compute_workload(&calendar(), &now);
+    let weeks = workload
+        .by_week
+        .iter()
+        .map(|week| (week.monday.to_string(), week.hours.total().num_minutes()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        weeks,
+        vec![
+            ("2025-01-13".to_owned(), 210),
+            ("2025-01-20".to_owned(), 120),
+            ("2025-02-03".to_owned(), 120),
+        ]
+    );
+
+    assert_eq!(workload.by_semester.len(), 2);
+    let first = &workload.by_semester[0];
+    assert_eq!(first.semester.start, CyuDate::new(2024, 9, 1).unwrap());
+    assert_eq!(first.semester.end, CyuDate::new(2025, 1, 31).unwrap());
+    assert_eq!(first.hours, hours(330, 0));
+    let second = &workload.by_semester[1];
+    assert_eq!(second.semester.end, CyuDate::new(2025, 8, 31).unwrap());
+    assert_eq!(second.hours, hours(120, 0));
+}
+
+#[test]
+fn serialized_in_hours() {
+    let now = CyuDateTime::new(2025, 1, 13, 9, 0, 0).unwrap();
+    let workload = compute_workload(&calendar()[..1], &now);
+    assert_eq!(
+        serde_json::to_value(workload.total).unwrap(),
+        json!({ "done": 0.5, "left": 1.0, "total": 1.5 })
+    );
+}
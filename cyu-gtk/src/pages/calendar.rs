@@ -1,14 +1,13 @@
-use crate::utils::auth::{self, AUTH};
+use crate::utils::auth;
 use crate::utils::calendar_event::Event;
 use crate::utils::constants::APP_TITLE;
-use crate::utils::FETCHER;
+use crate::utils::SESSION;
 use crate::widgets::calendar_event::{CalendarEventWidget, CalendarEventWidgetOutput};
 use crate::widgets::calendar_event_details::{
     CalendarEventDetailsWidget, CalendarEventDetailsWidgetInput,
 };
 use adw::BreakpointCondition;
-use async_recursion::async_recursion;
-use cyu_fetcher::calendar::{CalendarView, ColorBy, GetCalendarQuery};
+use cyu_fetcher::calendar::{CalendarView, ColorBy};
 use cyu_fetcher::errors::Error;
 use cyu_fetcher::utils::CyuDate;
 use relm4::factory::FactoryVecDeque;
@@ -19,8 +18,6 @@ pub struct CalendarPage {
     day_selector_widget: gtk::Calendar,
     event_details_widget: Controller<CalendarEventDetailsWidget>,
     split_view: adw::NavigationSplitView,
-
-    is_retrying: bool,
 }
 
 fn gdatetime_to_cyudate(datetime: gtk::glib::DateTime) -> CyuDate {
@@ -31,27 +28,12 @@ fn gdatetime_to_cyudate(datetime: gtk::glib::DateTime) -> CyuDate {
 }
 
 impl CalendarPage {
-    #[async_recursion(?Send)]
     async fn refresh(&mut self, sender: &AsyncComponentSender<Self>) {
-        let auth_lock = AUTH.read().unwrap();
-        let Some(auth) = auth_lock.as_ref() else {
-            return;
-        };
-
         let date = gdatetime_to_cyudate(self.day_selector_widget.date());
-        let calendar = FETCHER
-            .get_calendar(GetCalendarQuery {
-                id: auth.id.clone(),
-                token: auth.token.clone(),
-                start: date.clone(),
-                end: date,
-                view: CalendarView::Day,
-                color_by: ColorBy::Subject,
-            })
+        let calendar = SESSION
+            .get_calendar(date.clone(), date, CalendarView::Day, ColorBy::Subject)
             .await;
 
-        drop(auth_lock);
-
         match calendar {
             Ok(mut calendar) => {
                 let event_widgets = &mut self.event_widgets.guard();
@@ -61,28 +43,16 @@ impl CalendarPage {
                     event_widgets.push_back(event.clone());
                 }
             }
+            // The session already tried to log in again, the credentials
+            // themselves are refused
             Err(Error::Unauthorized) => {
-                if !self.is_retrying {
-                    self.is_retrying = true;
-                    let result = auth::refetch().await;
-                    if result.is_err() {
-                        auth::logout().await;
-                        sender
-                            .output(CalendarPageOutput::LoggedOut)
-                            .expect("Failed to send logout signal");
-                        return;
-                    }
-                    self.refresh(sender).await;
-                    self.is_retrying = false;
-                } else {
-                    auth::logout().await;
-                    sender
-                        .output(CalendarPageOutput::LoggedOut)
-                        .expect("Failed to send logout signal");
-                }
+                auth::logout().await;
+                sender
+                    .output(CalendarPageOutput::LoggedOut)
+                    .expect("Failed to send logout signal");
             }
             Err(err) => {
-                eprintln!("Failed to get calendar: {:?}", err);
+                eprintln!("Failed to get calendar: {}", err);
             }
         }
     }
@@ -174,8 +144,6 @@ impl SimpleAsyncComponent for CalendarPage {
             event_details_widget: CalendarEventDetailsWidget::builder().launch(None).detach(),
             day_selector_widget: gtk::Calendar::new(),
             split_view: adw::NavigationSplitView::default(),
-
-            is_retrying: false,
         };
         model.refresh(&sender).await;
 
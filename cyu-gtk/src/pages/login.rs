@@ -111,7 +111,7 @@ impl Component for LoginPage {
                     let command = match auth_result {
                         Ok(_) => LoginPageMessage::LoggedIn,
                         Err(auth::Error::BadCredentials) => LoginPageMessage::Unauthorized,
-                        Err(_) => LoginPageMessage::Error("An error occurred".into()),
+                        Err(auth::Error::Remote(err)) => LoginPageMessage::Error(err.to_string()),
                     };
                     sender
                         .command_sender()
@@ -1,16 +1,27 @@
-use crate::utils::{config::CONFIG, secret::SECRET, FETCHER};
+use crate::utils::{config::CONFIG, secret::SECRET, SESSION};
+use cyu_fetcher::{CredentialProvider, Credentials, SessionCookies};
+use futures::future::BoxFuture;
 use std::sync::RwLock;
 
 pub static AUTH: RwLock<Option<Auth>> = RwLock::new(None);
 
 pub enum Error {
-    Remote,
+    Remote(cyu_fetcher::Error),
     BadCredentials,
 }
 
+impl From<cyu_fetcher::Error> for Error {
+    fn from(err: cyu_fetcher::Error) -> Self {
+        match err {
+            cyu_fetcher::Error::Unauthorized => Self::BadCredentials,
+            err => Self::Remote(err),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Auth {
-    pub token: String,
+    pub session: SessionCookies,
     pub id: String,
     pub name: String,
     pub username: String,
@@ -27,17 +38,16 @@ pub async fn init() {
 }
 
 pub async fn login(username: String, password: String) -> Result<(), Error> {
-    let token = FETCHER
-        .login(username.clone().into(), password.clone().into())
-        .await
-        .map_err(|_| Error::Remote)?;
-    let infos = FETCHER
-        .get_infos(token.clone())
-        .await
-        .map_err(|_| Error::BadCredentials)?;
+    let identity = SESSION
+        .login_with(Credentials {
+            username: username.clone(),
+            password: password.clone(),
+        })
+        .await?;
+    let infos = SESSION.get_infos().await?;
     let auth = Auth {
-        token,
-        id: infos.federation_id,
+        session: identity.token,
+        id: identity.federation_id,
         name: infos.display_name,
         username,
         password,
@@ -52,12 +62,19 @@ pub async fn login(username: String, password: String) -> Result<(), Error> {
 
 pub async fn logout() {
     SECRET.remove_auth().await;
-    {
-        AUTH.write().unwrap().take();
-    }
+    AUTH.write().unwrap().take();
+    SESSION.logout().await;
 }
 
-pub async fn refetch() -> Result<(), Error> {
-    let auth = SECRET.get_auth().await.ok_or(Error::BadCredentials)?;
-    login(auth.username, auth.password).await
+/// Lets [`SESSION`] log in again with the credentials of the current user.
+pub struct AuthCredentials;
+
+impl CredentialProvider for AuthCredentials {
+    fn credentials(&self) -> BoxFuture<'_, Option<Credentials>> {
+        let credentials = AUTH.read().unwrap().as_ref().map(|auth| Credentials {
+            username: auth.username.clone(),
+            password: auth.password.clone(),
+        });
+        Box::pin(std::future::ready(credentials))
+    }
 }
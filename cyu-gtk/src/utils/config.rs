@@ -23,6 +23,15 @@ const fn default_save_credentials() -> bool {
 pub struct ConfigContent {
     #[serde(default = "default_save_credentials")]
     pub save_credentials: bool,
+    /// TOML file describing the Celcat instance to use, CYU when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile_file: Option<std::path::PathBuf>,
+    /// Overrides the base URL of the profile.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// TOML or JSON file adding sites to the ones of the profile.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sites_file: Option<std::path::PathBuf>,
 }
 
 impl ConfigContent {
@@ -54,6 +63,18 @@ impl Config {
         self.content.save_credentials
     }
 
+    pub fn profile_file(&self) -> Option<&std::path::Path> {
+        self.content.profile_file.as_deref()
+    }
+
+    pub fn base_url(&self) -> Option<&str> {
+        self.content.base_url.as_deref()
+    }
+
+    pub fn sites_file(&self) -> Option<&std::path::Path> {
+        self.content.sites_file.as_deref()
+    }
+
     pub fn set_save_credentials(&mut self, save_credentials: bool) {
         self.content.save_credentials = save_credentials;
         let Ok(content) = toml::to_string(&self.content) else {
@@ -1,4 +1,58 @@
-use cyu_fetcher::Fetcher;
+use super::auth::{AuthCredentials, AUTH};
+use super::config::CONFIG;
+use cyu_fetcher::sites::SiteRegistry;
+use cyu_fetcher::{CacheConfig, Fetcher, Identity, InstanceProfile, Session};
 use once_cell::sync::Lazy;
 
-pub static FETCHER: Lazy<Fetcher> = Lazy::new(Fetcher::new);
+/// The configured Celcat instance, CYU by default.
+pub static PROFILE: Lazy<InstanceProfile> = Lazy::new(|| {
+    let config = CONFIG.read().unwrap();
+    let mut profile = match config.profile_file() {
+        Some(path) => InstanceProfile::from_file(path).unwrap_or_else(|err| {
+            eprintln!("Failed to load profile, using CYU: {err}");
+            InstanceProfile::cyu().clone()
+        }),
+        None => InstanceProfile::cyu().clone(),
+    };
+    if let Some(base_url) = config.base_url() {
+        match base_url.parse() {
+            Ok(base_url) => profile.base_url = base_url,
+            Err(err) => eprintln!("Invalid base URL {base_url:?}, using the profile's: {err}"),
+        }
+    }
+    profile
+});
+
+pub static FETCHER: Lazy<Fetcher> = Lazy::new(|| {
+    Fetcher::builder()
+        .profile(&PROFILE)
+        .cache(CacheConfig::default())
+        .build()
+        .expect("failed to build fetcher")
+});
+
+/// Session of the logged in user, resumed from the saved one if any.
+pub static SESSION: Lazy<Session> = Lazy::new(|| {
+    let session = Session::new(FETCHER.clone(), AuthCredentials);
+    match AUTH.read().unwrap().as_ref() {
+        Some(auth) => session.resume(Identity {
+            token: auth.session.clone(),
+            federation_id: auth.id.clone(),
+        }),
+        None => session,
+    }
+});
+
+/// Sites of the profile, extended with the configured file if any.
+pub static SITES: Lazy<SiteRegistry> = Lazy::new(|| {
+    let Some(path) = CONFIG.read().unwrap().sites_file().map(ToOwned::to_owned) else {
+        return PROFILE.sites.clone();
+    };
+    match SiteRegistry::from_file(&path) {
+        Ok(sites) => PROFILE.sites.clone().merge(sites),
+        Err(err) => {
+            eprintln!("Failed to load sites file, using the sites of the profile: {err}");
+            PROFILE.sites.clone()
+        }
+    }
+});
@@ -5,4 +5,4 @@ pub mod constants;
 pub mod fetcher;
 pub mod secret;
 
-pub use fetcher::FETCHER;
+pub use fetcher::{SESSION, SITES};
@@ -70,13 +70,19 @@ impl Secret {
     }
 
     pub async fn get_auth(&self) -> Option<Auth> {
-        let token = self.get("token").await?;
+        // Missing when saved by a version storing raw tokens, the session
+        // then logs in again on first use
+        let session = self
+            .get("session")
+            .await
+            .and_then(|session| serde_json::from_str(&session).ok())
+            .unwrap_or_default();
         let id = self.get("id").await?;
         let name = self.get("name").await?;
         let username = self.get("username").await?;
         let password = self.get("password").await?;
         Some(Auth {
-            token,
+            session,
             id,
             name,
             username,
@@ -85,7 +91,10 @@ impl Secret {
     }
 
     pub async fn set_auth(&self, auth: Auth) {
-        self.set("token", auth.token).await;
+        match serde_json::to_string(&auth.session) {
+            Ok(session) => self.set("session", session).await,
+            Err(_) => eprintln!("Failed to serialize session"),
+        }
         self.set("id", auth.id).await;
         self.set("name", auth.name).await;
         self.set("username", auth.username).await;
@@ -93,6 +102,7 @@ impl Secret {
     }
 
     pub async fn remove_auth(&self) {
+        self.remove("session").await;
         self.remove("token").await;
         self.remove("id").await;
         self.remove("name").await;
@@ -1,5 +1,6 @@
 use crate::utils::calendar_event::Event;
 use crate::widgets::calendar_event_map::CalendarEventMap;
+use cyu_fetcher::calendar::EventDetails;
 use relm4::{adw::prelude::*, prelude::*};
 use relm4::{ComponentParts, ComponentSender, SimpleComponent};
 
@@ -7,6 +8,7 @@ use super::calendar_event_map::CalendarEventMapInput;
 
 pub struct CalendarEventDetailsWidget {
     event: Option<Event>,
+    details: EventDetails,
     stack_widget: gtk::Stack,
     event_widget: adw::Clamp,
     no_event_widget: adw::StatusPage,
@@ -40,6 +42,55 @@ impl SimpleComponent for CalendarEventDetailsWidget {
                         set_valign: gtk::Align::Start,
                         add_css_class: "boxed-list",
                         set_selection_mode: gtk::SelectionMode::None,
+                        adw::ActionRow {
+                            set_title: "Type",
+                            #[watch]
+                            set_subtitle: model.event
+                                .as_ref()
+                                .map(|event| event.event_category().display_name())
+                                .unwrap_or_default(),
+                            add_css_class: "property",
+                        },
+                        adw::ActionRow {
+                            set_title: "Module",
+                            #[watch]
+                            set_visible: model.details.module.is_some(),
+                            #[watch]
+                            set_subtitle: model.details.module.as_deref().unwrap_or_default(),
+                            add_css_class: "property",
+                        },
+                        adw::ActionRow {
+                            set_title: "Salle",
+                            #[watch]
+                            set_visible: !model.details.rooms.is_empty(),
+                            #[watch]
+                            set_subtitle: &model.details.rooms.join(", "),
+                            add_css_class: "property",
+                        },
+                        adw::ActionRow {
+                            set_title: "Enseignant",
+                            #[watch]
+                            set_visible: !model.details.teachers.is_empty(),
+                            #[watch]
+                            set_subtitle: &model.details.teachers.join(", "),
+                            add_css_class: "property",
+                        },
+                        adw::ActionRow {
+                            set_title: "Groupes",
+                            #[watch]
+                            set_visible: !model.details.groups.is_empty(),
+                            #[watch]
+                            set_subtitle: &model.details.groups.join(", "),
+                            add_css_class: "property",
+                        },
+                        adw::ActionRow {
+                            set_title: "Remarques",
+                            #[watch]
+                            set_visible: !model.details.remarks.is_empty(),
+                            #[watch]
+                            set_subtitle: &model.details.remarks.join("\n"),
+                            add_css_class: "property",
+                        },
                         adw::ActionRow {
                             set_title: "Description",
                             #[watch]
@@ -104,6 +155,10 @@ impl SimpleComponent for CalendarEventDetailsWidget {
         _sender: ComponentSender<Self>,
     ) -> ComponentParts<Self> {
         let model = Self {
+            details: event
+                .as_ref()
+                .map(|event| event.details())
+                .unwrap_or_default(),
             event,
             stack_widget: root.clone(),
             event_widget: adw::Clamp::default(),
@@ -131,6 +186,10 @@ impl SimpleComponent for CalendarEventDetailsWidget {
                 } else {
                     self.stack_widget.set_visible_child(&self.no_event_widget);
                 }
+                self.details = event
+                    .as_ref()
+                    .map(|event| event.details())
+                    .unwrap_or_default();
                 self.event = event;
             }
         }
@@ -1,5 +1,5 @@
 use crate::utils::calendar_event::Event;
-use cyu_fetcher::calendar::{LOCATIONS_COORD, NB_LOCATIONS};
+use crate::utils::SITES;
 use libshumate::prelude::*;
 use relm4::{gtk, prelude::*, SimpleComponent};
 
@@ -9,9 +9,17 @@ pub struct CalendarEventMap {
 }
 impl CalendarEventMap {
     fn update(&self) {
-        let Some(coords) = self.event.as_ref().and_then(|event| event.coords()) else {
+        let Some(event) = &self.event else {
             return;
         };
+        let coords = match SITES.locate(event) {
+            Ok(Some(location)) => location.coordinates(),
+            Ok(None) => return,
+            Err(unknown) => {
+                eprintln!("Cannot show event {} on the map: {unknown}", event.id());
+                return;
+            }
+        };
         let Some(viewport) = self.map_widget.viewport() else {
             return;
         };
@@ -57,18 +65,16 @@ impl SimpleComponent for CalendarEventMap {
             libshumate::MapSourceRegistry::with_defaults().by_id(libshumate::MAP_SOURCE_OSM_MAPNIK);
         model.map_widget.set_map_source(map_source.as_ref());
         if let Some(viewport) = model.map_widget.viewport() {
-            let path_layer = libshumate::PathLayer::new(&viewport);
             let marker_layer = libshumate::MarkerLayer::new(&viewport);
-            for i in 0..NB_LOCATIONS {
+            for site in SITES.sites() {
                 let icon = gtk::Image::from_resource(
                     "/fr/poco/cyu-gtk/icons/private/hicolor/scalable/apps/maps-mark-location.svg",
                 );
                 icon.set_pixel_size(35);
                 let marker = libshumate::Marker::new();
-                marker.set_location(LOCATIONS_COORD[i][0], LOCATIONS_COORD[i][1]);
+                marker.set_location(site.coordinates[0], site.coordinates[1]);
                 marker.set_child(Some(&icon));
                 marker_layer.add_marker(&marker);
-                path_layer.add_node(&marker);
             }
             model.map_widget.add_overlay_layer(&marker_layer);
         }
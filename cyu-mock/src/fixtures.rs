@@ -0,0 +1,158 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::Serialize;
+
+/// Data served by the mock server. Every field can be changed while the
+/// server is running through [`crate::MockServer::update`].
+#[derive(Debug, Clone)]
+pub struct Fixtures {
+    pub username: String,
+    pub password: String,
+    pub federation_id: String,
+    pub display_name: String,
+    pub date_extents: (NaiveDate, NaiveDate),
+    /// Timetable of the student.
+    pub events: Vec<Event>,
+    /// Other timetables: rooms, teachers, groups, ...
+    pub resources: Vec<Resource>,
+    /// `GetCalendarData` requests covering one of these days fail.
+    pub failing_days: Vec<NaiveDate>,
+    /// Number of upcoming `GetCalendarData` requests that fail.
+    pub transient_failures: usize,
+    /// `ReadResourceListItems` ignores `pageNumber` and leaves out `total`,
+    /// always answering with the first page.
+    pub unpaged_search: bool,
+    /// Every request of a logged in user replaces their session cookie by
+    /// a new one, the old one being forgotten.
+    pub renew_sessions: bool,
+}
+
+impl Default for Fixtures {
+    fn default() -> Self {
+        Self {
+            username: String::from("e-student"),
+            password: String::from("password"),
+            federation_id: String::from("22001234"),
+            display_name: String::from("STUDENT Jane"),
+            date_extents: (
+                NaiveDate::from_ymd_opt(2024, 9, 2).unwrap(),
+                NaiveDate::from_ymd_opt(2025, 8, 31).unwrap(),
+            ),
+            events: Vec::new(),
+            resources: Vec::new(),
+            failing_days: Vec::new(),
+            transient_failures: 0,
+            unpaged_search: false,
+            renew_sessions: false,
+        }
+    }
+}
+
+/// A timetable other than the student's.
+#[derive(Debug, Clone)]
+pub struct Resource {
+    /// `resType` code, 102 for a room for instance.
+    pub resource_type: u16,
+    pub id: String,
+    pub name: String,
+    pub events: Vec<Event>,
+}
+
+/// An event as returned by `Home/GetCalendarData`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Event {
+    pub id: String,
+    #[serde(serialize_with = "serialize_datetime")]
+    pub start: NaiveDateTime,
+    #[serde(serialize_with = "serialize_optional_datetime")]
+    pub end: Option<NaiveDateTime>,
+    pub all_day: bool,
+    pub description: String,
+    pub background_color: String,
+    pub text_color: String,
+    pub department: String,
+    pub faculty: Option<String>,
+    pub event_category: String,
+    pub sites: Option<Vec<String>>,
+    pub modules: Option<Vec<String>>,
+}
+
+impl Event {
+    /// Builds a timed event. `lines` are joined the way Celcat does, with
+    /// `<br />` and CRLF, the category coming first.
+    pub fn new(
+        id: impl Into<String>,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+        category: impl Into<String>,
+        lines: &[&str],
+    ) -> Self {
+        let category = category.into();
+        let description = std::iter::once(category.as_str())
+            .chain(lines.iter().copied())
+            .map(|line| format!("{line}<br />\r\n"))
+            .collect::<Vec<_>>()
+            .join("<br />\r\n");
+        Self {
+            id: id.into(),
+            start,
+            end: Some(end),
+            all_day: false,
+            description,
+            background_color: String::from("#7D4F72"),
+            text_color: String::from("#FFFFFF"),
+            department: String::from("CY Tech"),
+            faculty: None,
+            event_category: category,
+            sites: None,
+            modules: None,
+        }
+    }
+
+    pub fn with_sites(mut self, sites: &[&str]) -> Self {
+        self.sites = Some(sites.iter().map(ToString::to_string).collect());
+        self
+    }
+
+    pub fn with_modules(mut self, modules: &[&str]) -> Self {
+        self.modules = Some(modules.iter().map(ToString::to_string).collect());
+        self
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    pub fn all_day(mut self) -> Self {
+        self.all_day = true;
+        self
+    }
+
+    pub fn without_end(mut self) -> Self {
+        self.end = None;
+        self
+    }
+
+    pub(crate) fn overlaps(&self, start: NaiveDate, end: NaiveDate) -> bool {
+        let event_end = self.end.unwrap_or(self.start);
+        self.start.date() <= end && event_end.date() >= start
+    }
+}
+
+fn serialize_datetime<S: serde::Serializer>(
+    datetime: &NaiveDateTime,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&datetime.format("%Y-%m-%dT%H:%M:%S").to_string())
+}
+
+fn serialize_optional_datetime<S: serde::Serializer>(
+    datetime: &Option<NaiveDateTime>,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    match datetime {
+        Some(datetime) => serialize_datetime(datetime, s),
+        None => s.serialize_none(),
+    }
+}
@@ -0,0 +1,168 @@
+//! A local stand-in for the CYU Celcat calendar.
+//!
+//! Only the endpoints scraped by `cyu-fetcher` are served, with the same
+//! markup and cookies, so the fetcher and its frontends can be exercised
+//! without network access.
+
+mod fixtures;
+mod routes;
+
+pub use fixtures::{Event, Fixtures, Resource};
+
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+#[derive(Clone, Default)]
+pub(crate) struct State {
+    fixtures: Arc<RwLock<Fixtures>>,
+    sessions: Arc<RwLock<HashSet<String>>>,
+    session_counter: Arc<AtomicUsize>,
+    tickets: Arc<Mutex<HashSet<String>>>,
+    hits: Arc<Mutex<HashMap<String, usize>>>,
+}
+
+impl State {
+    pub(crate) fn fixtures(&self) -> RwLockReadGuard<'_, Fixtures> {
+        self.fixtures.read().unwrap()
+    }
+
+    pub(crate) fn fixtures_mut(&self) -> RwLockWriteGuard<'_, Fixtures> {
+        self.fixtures.write().unwrap()
+    }
+
+    pub(crate) fn hit(&self, path: &str) {
+        *self
+            .hits
+            .lock()
+            .unwrap()
+            .entry(path.to_owned())
+            .or_default() += 1;
+    }
+
+    pub(crate) fn verification_token(&self) -> String {
+        String::from("mock-verification-token")
+    }
+
+    pub(crate) fn open_session(&self) -> String {
+        let id = self.session_counter.fetch_add(1, Ordering::Relaxed);
+        let session = format!("mock-session-{id}");
+        self.sessions.write().unwrap().insert(session.clone());
+        session
+    }
+
+    pub(crate) fn has_session(&self, session: &str) -> bool {
+        self.sessions.read().unwrap().contains(session)
+    }
+
+    /// Replaces `session` by a new one, `None` if it was not open.
+    pub(crate) fn renew_session(&self, session: &str) -> Option<String> {
+        if !self.sessions.write().unwrap().remove(session) {
+            return None;
+        }
+        Some(self.open_session())
+    }
+
+    /// A service ticket, valid once.
+    pub(crate) fn issue_ticket(&self) -> String {
+        let id = self.session_counter.fetch_add(1, Ordering::Relaxed);
+        let ticket = format!("ST-{id}-mock");
+        self.tickets.lock().unwrap().insert(ticket.clone());
+        ticket
+    }
+
+    pub(crate) fn redeem_ticket(&self, ticket: &str) -> bool {
+        self.tickets.lock().unwrap().remove(ticket)
+    }
+}
+
+/// A running mock server, stopped when dropped.
+pub struct MockServer {
+    addr: SocketAddr,
+    state: State,
+    handle: JoinHandle<()>,
+}
+
+impl MockServer {
+    /// Binds a random local port and starts serving `fixtures`.
+    /// Must be called from within a tokio runtime.
+    pub async fn start(fixtures: Fixtures) -> Self {
+        let state = State {
+            fixtures: Arc::new(RwLock::new(fixtures)),
+            ..Default::default()
+        };
+        let listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+            .await
+            .expect("failed to bind mock server");
+        let addr = listener.local_addr().expect("failed to get mock address");
+        let app = routes::routes()
+            .layer(axum::middleware::from_fn_with_state(
+                state.clone(),
+                routes::renew_sessions,
+            ))
+            .layer(axum::middleware::from_fn_with_state(
+                state.clone(),
+                routes::count_hits,
+            ))
+            .with_state(state.clone());
+        let handle = tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("mock server failed");
+        });
+        Self {
+            addr,
+            state,
+            handle,
+        }
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Value to give to `FetcherBuilder::base_url`.
+    pub fn base_url(&self) -> String {
+        format!("http://{}/calendar", self.addr)
+    }
+
+    /// Value to give to `CasLogin::new`, a CAS server logging in to the
+    /// calendar with the same credentials as its LDAP form.
+    pub fn cas_url(&self) -> String {
+        format!("http://{}/cas", self.addr)
+    }
+
+    pub fn update(&self, f: impl FnOnce(&mut Fixtures)) {
+        f(&mut self.state.fixtures.write().unwrap());
+    }
+
+    /// Number of requests received on `path`, relative to the base URL
+    /// (`/Home/GetCalendarData` for instance).
+    pub fn hits(&self, path: &str) -> usize {
+        self.raw_hits(&format!("/calendar{path}"))
+    }
+
+    /// Number of requests received on `path`, relative to the server root
+    /// (`/cas/login` for instance).
+    pub fn raw_hits(&self, path: &str) -> usize {
+        self.state
+            .hits
+            .lock()
+            .unwrap()
+            .get(path)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Forgets every session, as CYU does when its cookies expire.
+    pub fn expire_sessions(&self) {
+        self.state.sessions.write().unwrap().clear();
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
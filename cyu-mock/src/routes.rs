@@ -0,0 +1,433 @@
+use crate::State as MockState;
+use axum::body::Bytes;
+use axum::extract::{RawQuery, Request, State};
+use axum::http::header::{COOKIE, LOCATION, SET_COOKIE};
+use axum::http::{HeaderMap, StatusCode};
+use axum::middleware::Next;
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{Datelike as _, NaiveDate};
+use serde_json::json;
+
+pub(crate) const VERIFICATION_COOKIE: &str = "__RequestVerificationToken_L2NhbGVuZGFy";
+pub(crate) const SESSION_COOKIE: &str = ".AspNetCore.Cookies";
+/// Ticket-granting cookie of the CAS server.
+const CAS_COOKIE: &str = "TGC";
+const CAS_EXECUTION: &str = "mock-execution";
+/// `resType` of the logged in student's timetable.
+const STUDENT: u16 = 104;
+
+fn cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+fn is_authed(state: &MockState, headers: &HeaderMap) -> bool {
+    cookie(headers, SESSION_COOKIE).is_some_and(|session| state.has_session(session))
+}
+
+fn form(body: &[u8]) -> Vec<(String, String)> {
+    serde_urlencoded::from_bytes(body).unwrap_or_default()
+}
+
+fn form_value<'a>(form: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    form.iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, value)| value.as_str())
+}
+
+pub(crate) async fn count_hits(
+    State(state): State<MockState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    state.hit(request.uri().path());
+    next.run(request).await
+}
+
+/// Gives a new session cookie to logged in users when
+/// [`crate::Fixtures::renew_sessions`] is set.
+pub(crate) async fn renew_sessions(
+    State(state): State<MockState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let session = cookie(request.headers(), SESSION_COOKIE).map(str::to_owned);
+    let mut response = next.run(request).await;
+    if !state.fixtures().renew_sessions {
+        return response;
+    }
+    let renewed = session.and_then(|session| state.renew_session(&session));
+    if let Some(renewed) = renewed {
+        let cookie = format!("{SESSION_COOKIE}={renewed}; path=/; HttpOnly");
+        if let Ok(value) = cookie.parse() {
+            response.headers_mut().append(SET_COOKIE, value);
+        }
+    }
+    response
+}
+
+fn redirect_to_login() -> Response {
+    (StatusCode::FOUND, [(LOCATION, "/calendar/Login")]).into_response()
+}
+
+async fn login_page(State(state): State<MockState>) -> Response {
+    let token = state.verification_token();
+    (
+        [(
+            SET_COOKIE,
+            format!("{VERIFICATION_COOKIE}={token}; path=/calendar; HttpOnly"),
+        )],
+        Html(format!(
+            r#"<!DOCTYPE html>
+<html>
+<body>
+    <form action="/calendar/LdapLogin/Logon" method="post">
+        <input id="Name" name="Name" type="text" value="" />
+        <input id="Password" name="Password" type="password" />
+        <input type="hidden" value="{token}" name="__RequestVerificationToken">
+    </form>
+</body>
+</html>"#
+        )),
+    )
+        .into_response()
+}
+
+async fn logon(State(state): State<MockState>, headers: HeaderMap, body: Bytes) -> Response {
+    let form = form(&body);
+    let token = state.verification_token();
+    let has_token = form_value(&form, "__RequestVerificationToken") == Some(token.as_str())
+        && cookie(&headers, VERIFICATION_COOKIE) == Some(token.as_str());
+    if !has_token {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    let valid = {
+        let fixtures = state.fixtures();
+        form_value(&form, "Name") == Some(fixtures.username.as_str())
+            && form_value(&form, "Password") == Some(fixtures.password.as_str())
+    };
+    if !valid {
+        return login_page(State(state)).await;
+    }
+
+    let session = state.open_session();
+    (
+        StatusCode::FOUND,
+        [
+            (LOCATION, String::from("/calendar/")),
+            (
+                SET_COOKIE,
+                format!("{SESSION_COOKIE}={session}; path=/; HttpOnly"),
+            ),
+        ],
+    )
+        .into_response()
+}
+
+fn service(query: Option<&str>) -> Option<String> {
+    let query = serde_urlencoded::from_str::<Vec<(String, String)>>(query.unwrap_or("")).ok()?;
+    form_value(&query, "service").map(str::to_owned)
+}
+
+/// An Apereo CAS login page, posting to itself.
+fn cas_login_form(service: &str) -> Html<String> {
+    let action = serde_urlencoded::to_string([("service", service)]).unwrap_or_default();
+    Html(format!(
+        r#"<!DOCTYPE html>
+<html>
+<body>
+    <form method="post" id="fm1" action="login?{action}">
+        <input id="username" name="username" type="text" value="" />
+        <input id="password" name="password" type="password" value="" />
+        <input type="hidden" name="execution" value="{CAS_EXECUTION}" />
+        <input type="hidden" name="_eventId" value="submit" />
+        <input type="submit" name="submit" value="SE CONNECTER" />
+    </form>
+</body>
+</html>"#
+    ))
+}
+
+async fn cas_login_page(RawQuery(query): RawQuery) -> Response {
+    match service(query.as_deref()) {
+        Some(service) => cas_login_form(&service).into_response(),
+        None => StatusCode::BAD_REQUEST.into_response(),
+    }
+}
+
+async fn cas_logon(
+    State(state): State<MockState>,
+    RawQuery(query): RawQuery,
+    body: Bytes,
+) -> Response {
+    let form = form(&body);
+    let Some(service) = service(query.as_deref()) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    if form_value(&form, "execution") != Some(CAS_EXECUTION)
+        || form_value(&form, "_eventId") != Some("submit")
+    {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+
+    let valid = {
+        let fixtures = state.fixtures();
+        form_value(&form, "username") == Some(fixtures.username.as_str())
+            && form_value(&form, "password") == Some(fixtures.password.as_str())
+    };
+    if !valid {
+        return (StatusCode::UNAUTHORIZED, cas_login_form(&service)).into_response();
+    }
+
+    let ticket = state.issue_ticket();
+    (
+        StatusCode::FOUND,
+        [
+            (LOCATION, format!("{service}?ticket={ticket}")),
+            (
+                SET_COOKIE,
+                format!("{CAS_COOKIE}=TGT-{ticket}; path=/cas; HttpOnly"),
+            ),
+        ],
+    )
+        .into_response()
+}
+
+/// Celcat side of the CAS flow, opening a session for a valid ticket.
+async fn cas_service(State(state): State<MockState>, RawQuery(query): RawQuery) -> Response {
+    let query = serde_urlencoded::from_str::<Vec<(String, String)>>(query.as_deref().unwrap_or(""))
+        .unwrap_or_default();
+    let redeemed = form_value(&query, "ticket").is_some_and(|ticket| state.redeem_ticket(ticket));
+    if !redeemed {
+        return (StatusCode::FOUND, [(LOCATION, "/cas/login")]).into_response();
+    }
+
+    let session = state.open_session();
+    (
+        StatusCode::FOUND,
+        [
+            (LOCATION, String::from("/calendar/")),
+            (
+                SET_COOKIE,
+                format!("{SESSION_COOKIE}={session}; path=/; HttpOnly"),
+            ),
+        ],
+    )
+        .into_response()
+}
+
+async fn home(
+    State(state): State<MockState>,
+    headers: HeaderMap,
+    RawQuery(query): RawQuery,
+) -> Response {
+    // Celcat renders the login form in place of the home page
+    if !is_authed(&state, &headers) {
+        return login_page(State(state)).await;
+    }
+    let fixtures = state.fixtures();
+    let query = serde_urlencoded::from_str::<Vec<(String, String)>>(query.as_deref().unwrap_or(""))
+        .unwrap_or_default();
+
+    if form_value(&query, "CalendarViewType") == Some("Month") {
+        let code = form_value(&query, "EntityTypeAsIntegerString")
+            .and_then(|code| code.parse::<u16>().ok())
+            .filter(|&code| entity_type(code) == form_value(&query, "EntityType"));
+        let id = form_value(&query, "FederationIds").unwrap_or_default();
+        let known = match code {
+            Some(STUDENT) => id == fixtures.federation_id,
+            Some(code) => fixtures
+                .resources
+                .iter()
+                .any(|resource| resource.resource_type == code && resource.id == id),
+            None => false,
+        };
+        if !known {
+            return StatusCode::BAD_REQUEST.into_response();
+        }
+        let (earliest, latest) = fixtures.date_extents;
+        return Html(format!(
+            "<script>\n    var dateExtents = {{\n        earliest: {},\n        latest: {}\n    }};\n</script>",
+            js_date(earliest),
+            js_date(latest),
+        ))
+        .into_response();
+    }
+
+    Html(format!(
+        "<script>\r\n        var federationIdStr = '{}';\r\n</script>",
+        fixtures.federation_id
+    ))
+    .into_response()
+}
+
+/// `EntityType` Celcat pairs with each `resType` code on the home page.
+fn entity_type(code: u16) -> Option<&'static str> {
+    match code {
+        100 => Some("Module"),
+        101 => Some("Staff"),
+        102 => Some("Room"),
+        103 => Some("Group"),
+        104 => Some("Student"),
+        _ => None,
+    }
+}
+
+fn js_date(date: NaiveDate) -> String {
+    format!(
+        "new Date({}, {} - 1, {})",
+        date.year(),
+        date.month(),
+        date.day()
+    )
+}
+
+async fn load_display_names(
+    State(state): State<MockState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    if !is_authed(&state, &headers) {
+        return redirect_to_login();
+    }
+    let fixtures = state.fixtures();
+    let form = form(&body);
+    let resource_type = form_value(&form, "resType").and_then(|code| code.parse().ok());
+    let names = form
+        .iter()
+        .filter(|(key, _)| key == "federationIds[]")
+        .filter_map(|(_, id)| {
+            let name = match resource_type {
+                Some(STUDENT) if *id == fixtures.federation_id => &fixtures.display_name,
+                _ => fixtures
+                    .resources
+                    .iter()
+                    .find(|resource| {
+                        Some(resource.resource_type) == resource_type && resource.id == *id
+                    })
+                    .map(|resource| &resource.name)?,
+            };
+            Some(json!({ "federationId": id, "displayName": name }))
+        })
+        .collect::<Vec<_>>();
+    Json(names).into_response()
+}
+
+async fn read_resource_list_items(
+    State(state): State<MockState>,
+    headers: HeaderMap,
+    RawQuery(query): RawQuery,
+) -> Response {
+    if !is_authed(&state, &headers) {
+        return redirect_to_login();
+    }
+    let fixtures = state.fixtures();
+    let query = serde_urlencoded::from_str::<Vec<(String, String)>>(query.as_deref().unwrap_or(""))
+        .unwrap_or_default();
+    let Some(resource_type) =
+        form_value(&query, "resType").and_then(|code| code.parse::<u16>().ok())
+    else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    let term = form_value(&query, "searchTerm")
+        .unwrap_or_default()
+        .to_lowercase();
+    let page_size = form_value(&query, "pageSize")
+        .and_then(|size| size.parse().ok())
+        .unwrap_or(usize::MAX);
+    let page_number = form_value(&query, "pageNumber")
+        .and_then(|number| number.parse::<usize>().ok())
+        .filter(|_| !fixtures.unpaged_search)
+        .unwrap_or(1);
+
+    let results = fixtures
+        .resources
+        .iter()
+        .filter(|resource| resource.resource_type == resource_type)
+        .filter(|resource| resource.name.to_lowercase().contains(&term))
+        .map(|resource| json!({ "id": resource.id, "text": resource.name, "dept": "CY Tech" }))
+        .collect::<Vec<_>>();
+    let total = results.len();
+    let page = results
+        .into_iter()
+        .skip(page_size.saturating_mul(page_number.saturating_sub(1)))
+        .take(page_size)
+        .collect::<Vec<_>>();
+    if fixtures.unpaged_search {
+        return Json(json!({ "results": page })).into_response();
+    }
+    Json(json!({ "total": total, "results": page })).into_response()
+}
+
+async fn get_calendar_data(
+    State(state): State<MockState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    // Celcat answers an expired session with an empty body
+    if !is_authed(&state, &headers) {
+        return StatusCode::OK.into_response();
+    }
+    let form = form(&body);
+    let parse_date = |key| {
+        form_value(&form, key).and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+    };
+    let (Some(start), Some(end)) = (parse_date("start"), parse_date("end")) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+
+    {
+        let mut fixtures = state.fixtures_mut();
+        if fixtures.transient_failures > 0 {
+            fixtures.transient_failures -= 1;
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+        let range = start..=end;
+        if fixtures.failing_days.iter().any(|day| range.contains(day)) {
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    }
+
+    let fixtures = state.fixtures();
+    let resource_type = form_value(&form, "resType").and_then(|code| code.parse().ok());
+    let id = form_value(&form, "federationIds[]").unwrap_or_default();
+    let timetable = match resource_type {
+        Some(STUDENT) if id == fixtures.federation_id => &fixtures.events[..],
+        Some(resource_type) => fixtures
+            .resources
+            .iter()
+            .find(|resource| resource.resource_type == resource_type && resource.id == id)
+            .map_or(&[][..], |resource| &resource.events[..]),
+        None => return StatusCode::BAD_REQUEST.into_response(),
+    };
+    let events = timetable
+        .iter()
+        .filter(|event| event.overlaps(start, end))
+        .collect::<Vec<_>>();
+    Json(events).into_response()
+}
+
+pub(crate) fn routes() -> Router<MockState> {
+    Router::new()
+        .route("/calendar", get(home))
+        .route("/calendar/", get(home))
+        .route("/calendar/LdapLogin", get(login_page))
+        .route("/calendar/LdapLogin/Logon", post(logon))
+        .route("/calendar/CasLogin", get(cas_service))
+        .route("/calendar/Home/LoadDisplayNames", post(load_display_names))
+        .route(
+            "/calendar/Home/ReadResourceListItems",
+            get(read_resource_list_items),
+        )
+        .route("/calendar/Home/GetCalendarData", post(get_calendar_data))
+        .route("/cas/login", get(cas_login_page).post(cas_logon))
+}